@@ -0,0 +1,54 @@
+use egui::Pos2;
+
+const STORAGE_KEY: &str = "ghost";
+
+/// Path of the best scoring shot so far, persisted in the app storage.
+#[derive(Clone, Default)]
+pub struct Ghost {
+    /// points the shot was worth
+    pub points: usize,
+    pub path: Vec<Pos2>,
+}
+
+impl Ghost {
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        storage
+            .and_then(|s| s.get_string(STORAGE_KEY))
+            .and_then(|text| parse_ghost(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(STORAGE_KEY, write_ghost(self));
+    }
+
+    /// keeps the shot if it is worth at least as much as the current one,
+    /// so the most recent of equally good shots is shown
+    pub fn offer(&mut self, points: usize, path: &[Pos2]) {
+        if path.len() > 1 && points >= self.points {
+            self.points = points;
+            self.path = path.to_vec();
+        }
+    }
+}
+
+/// points in the first line, then one `x y` line per position
+fn write_ghost(ghost: &Ghost) -> String {
+    let mut text = format!("{}\n", ghost.points);
+    for pos in &ghost.path {
+        text.push_str(&format!("{} {}\n", pos.x, pos.y));
+    }
+    text
+}
+
+fn parse_ghost(text: &str) -> Option<Ghost> {
+    let mut lines = text.lines();
+    let points = lines.next()?.parse().ok()?;
+    let path = lines
+        .filter_map(|line| {
+            let (x, y) = line.split_once(' ')?;
+            Some(Pos2::new(x.parse().ok()?, y.parse().ok()?))
+        })
+        .collect();
+    Some(Ghost { points, path })
+}
@@ -1,37 +1,447 @@
-use egui::{Color32, Key, Pos2, Vec2};
+mod ghost;
+mod physics;
+
+use common::{Achievements, Canvas, Screenshot, Settings, arc, draw_game_over};
+use egui::{Color32, Key, PointerButton, Pos2, Rect, Vec2};
+use egui_game::utils::random_u32;
 use egui_game::{Anchor, DrawContext, EguiGame, Game, ImageFit, UpdateContext};
 use geo::{Intersects, Line};
+use ghost::Ghost;
+use physics::{Contact, PAD_KICK, Physics, Preset};
+
+/// toggles that survive a reset
+#[derive(Clone, Default)]
+struct Options {
+    moving_hoop: bool,
+    /// extra hoops worth more points
+    multi_hoop: bool,
+    /// score as much as possible before the time runs out, no lives
+    timed: bool,
+    /// two players take turns, no lives
+    versus: bool,
+    wind: bool,
+    /// leaves out the streaks that show the wind
+    hide_streaks: bool,
+    /// bar between the pad and the hoop that the ball bounces off
+    obstacle: bool,
+    /// launch from a cannon on the left instead of the trampoline
+    cannon: bool,
+    /// floating rings on the way to the hoop that give bonus points
+    rings: bool,
+    /// shows the path of the best shot
+    ghost: bool,
+    /// extra balls in play next to the main one
+    multi_ball: bool,
+    /// raised platforms the ball lands and bounces on like the floor
+    platforms: bool,
+    preset: Preset,
+    pad_size: PadSize,
+    pad_speed: PadSpeed,
+    skin: Skin,
+}
+
+/// ball sprite, cosmetic only
+#[derive(Clone, Copy, Default)]
+enum Skin {
+    #[default]
+    Classic,
+    Soccer,
+    Beach,
+}
+
+impl Skin {
+    fn image(self) -> egui::ImageSource<'static> {
+        match self {
+            Skin::Classic => egui::include_image!("../assets/ball.png"),
+            Skin::Soccer => egui::include_image!("../assets/ball_soccer.png"),
+            Skin::Beach => egui::include_image!("../assets/ball_beach.png"),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Skin::Classic => Skin::Soccer,
+            Skin::Soccer => Skin::Beach,
+            Skin::Beach => Skin::Classic,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Skin::Classic => "classic",
+            Skin::Soccer => "soccer",
+            Skin::Beach => "beach",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Skin::Classic, Skin::Soccer, Skin::Beach]
+            .into_iter()
+            .find(|s| s.name() == name)
+    }
+}
+
+/// trampoline width as difficulty
+#[derive(Clone, Copy, Default)]
+enum PadSize {
+    Wide,
+    #[default]
+    Normal,
+    Narrow,
+}
+
+impl PadSize {
+    fn width(self) -> f32 {
+        match self {
+            PadSize::Wide => BALL_SIZE * 6.,
+            PadSize::Normal => BALL_SIZE * 4.,
+            PadSize::Narrow => BALL_SIZE * 2.5,
+        }
+    }
+
+    /// fraction of the hoop size lost per point
+    fn hoop_shrink(self) -> f32 {
+        match self {
+            PadSize::Wide => 0.005,
+            PadSize::Normal => 0.01,
+            PadSize::Narrow => 0.02,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            PadSize::Wide => PadSize::Normal,
+            PadSize::Normal => PadSize::Narrow,
+            PadSize::Narrow => PadSize::Wide,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            PadSize::Wide => "easy",
+            PadSize::Normal => "normal",
+            PadSize::Narrow => "hard",
+        }
+    }
+}
+
+/// how fast the trampoline moves
+#[derive(Clone, Copy, Default)]
+enum PadSpeed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+}
+
+impl PadSpeed {
+    /// pad movement in screen widths per second
+    fn speed(self) -> f32 {
+        match self {
+            PadSpeed::Slow => 0.7,
+            PadSpeed::Normal => 1.0,
+            PadSpeed::Fast => 1.4,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            PadSpeed::Slow => PadSpeed::Normal,
+            PadSpeed::Normal => PadSpeed::Fast,
+            PadSpeed::Fast => PadSpeed::Slow,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            PadSpeed::Slow => "slow",
+            PadSpeed::Normal => "normal",
+            PadSpeed::Fast => "fast",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [PadSpeed::Slow, PadSpeed::Normal, PadSpeed::Fast]
+            .into_iter()
+            .find(|s| s.name() == name)
+    }
+}
 
-// all positions are in fractions of the screen size
+// all positions are in fractions of the court size
 #[derive(Clone, Default)]
 struct Basketball {
+    options: Options,
+    physics: Physics,
     score: usize,
+    highscore: usize,
     ball_pos: Pos2,
     ball_speed: Vec2,
     hit: Option<f64>,
+    /// pointer position where the ball was grabbed for a drag launch
+    aim_start: Option<Pos2>,
+    aim_end: Pos2,
+    /// the last score went through the middle of the rim
+    swish: bool,
+    /// the ball bounced off a side wall since it was launched
+    touched_wall: bool,
+    /// the last score was banked off a side wall
+    bank_shot: bool,
+    /// game times and ball positions since the last launch
+    shot_path: Vec<(f64, Pos2)>,
+    /// game times and ball positions of the last scoring shot
+    last_made: Vec<(f64, Pos2)>,
+    /// time in s into `last_made` while it is replayed, the game waits meanwhile
+    highlight: Option<f64>,
+    ghost: Ghost,
+    /// consecutive scores without touching the floor
+    combo: usize,
+    /// longest combo of the round
+    best_combo: usize,
+    /// high score when the round started, beating it is shown at the end
+    start_highscore: usize,
+    /// draws the rims and the checked ball path
+    debug: bool,
+    /// ball path tested against the rims in the last frame, if any
+    debug_path: Option<Line<f32>>,
+    /// turns and scores of the two-player match
+    versus: Match,
+    /// launches by a charge, a drag or the cannon and bounces high enough
+    /// to reach a rim, and how many of them scored
+    attempts: usize,
+    made: usize,
+    /// a launched shot that hasn't scored, missed or come back to the pad yet
+    in_flight: bool,
+    lives: usize,
+    /// time the ball first touched the floor since the last pad contact
+    on_floor_since: Option<f64>,
+    game_over: bool,
+    /// physics, timers and input are halted
+    paused: bool,
+    /// full list of controls, the game waits while it is open
+    help: bool,
+    /// screen area of the court, set while drawing
+    court_rect: Rect,
+    screenshot: Screenshot,
+    /// remaining time in s of the timed challenge
+    time_left: f32,
     pad_pos: Pos2,
+    /// game time of the last full-strength pad bounce
+    last_strong_bounce: Option<f64>,
+    /// cannon elevation in rad above the horizontal
+    cannon_angle: f32,
+    /// the ball sits in the cannon waiting to be fired
+    loaded: bool,
+    /// power meter filled while holding space, 0 to 1
+    charge: f32,
+    charging: bool,
+    /// released power, applied on the next pad contact
+    launch_power: f32,
+    hoops: Vec<Hoop>,
+    rings: Vec<Ring>,
+    /// balls of the multi-ball mode, the main ball is not part of this
+    extra_balls: Vec<Ball>,
+    /// raised platforms besides the main floor
+    platforms: Vec<Rect>,
+    /// oscillation phase of the moving hoops
+    hoop_phase: f32,
+    /// points gained with the last score
+    last_points: usize,
+    /// index of the hoop hit by the last score
+    scored_hoop: usize,
+    /// ball spin in rad/s, positive is clockwise
+    spin: f32,
+    /// scaled time in s, timers use this so slow motion stretches them
+    game_time: f64,
+    /// remaining real time in s of slow motion after a score
+    slow_motion: f32,
+    settings: Settings,
+    achievements: Achievements,
+    /// drawn rotation of the ball
+    rotation: f32,
+    /// horizontal wind acceleration, positive blows to the right
+    wind: f32,
+    wind_target: f32,
+    /// time until the wind picks a new target
+    wind_timer: f32,
+    /// drifting streaks that show the wind, cosmetic only
+    streaks: Vec<Pos2>,
 }
 
+/// width to height of the court, wider windows get bars at the sides
+/// and taller ones at the top and bottom
+const COURT_ASPECT: f32 = 16. / 9.;
+/// width of a screenshot in pixels, the height follows the court aspect
+const SCREENSHOT_WIDTH: f32 = 960.;
+
 const FLOOR_HEIGHT: f32 = 0.1;
 
 const BALL_SIZE: f32 = 0.05;
 
 const HOOP_POS: Pos2 = Pos2::new(0.85, 0.5);
 const HOOP_SIZE: f32 = 0.1;
-
-const ACCELERATION: f32 = 1.5;
-const ELASTICITY: f32 = 0.9;
-const MIN_SPEED: f32 = 0.15;
+/// vertical travel of a moving hoop around its base position
+const HOOP_AMPLITUDE: f32 = 0.2;
+/// oscillation speed in rad/s, grows with the score
+const HOOP_SPEED: f32 = 1.0;
+/// smallest hoop size as a fraction of its starting size
+const MIN_HOOP_SCALE: f32 = 0.6;
 
 const HIT_DELAY: f64 = 1.0;
+/// time in s the score overlay takes to fade in and out
+const HIT_FADE: f64 = 0.25;
+
+/// ball speed below which it can be grabbed for a drag launch
+const GRAB_SPEED: f32 = 0.5;
+/// launch speed per dragged screen fraction
+const DRAG_POWER: f32 = 4.0;
+const MAX_LAUNCH_SPEED: f32 = 2.5;
+
+/// ends of the obstacle bar
+const OBSTACLE_START: Pos2 = Pos2::new(0.55, 0.6);
+const OBSTACLE_END: Pos2 = Pos2::new(0.7, 0.6);
+
+/// pad speed factor while the sprint key is held. Shift would be the natural
+/// choice, but the update context only has `key_down` for `egui::Key`, which
+/// has no modifier keys, and it doesn't pass on `Modifiers`
+const SPRINT_KEY: Key = Key::Z;
+const SPRINT_FACTOR: f32 = 2.0;
+/// time in s the pad needs to give a full-strength bounce again
+const PAD_RECHARGE: f64 = 1.5;
+/// fraction of the pad kick left while it is recharging
+const WEAK_KICK: f32 = 0.3;
+
+const LIVES: usize = 3;
+/// shots per player in a two-player match
+const MATCH_SHOTS: usize = 5;
+const CHALLENGE_TIME: f32 = 60.0;
+/// time in s the ball may stay on the floor before it counts as a miss
+const MISS_DELAY: f64 = 2.0;
+
+/// fraction of the rim around its center that counts as a clean shot
+const SWISH_ZONE: f32 = 0.5;
+const SWISH_BONUS: usize = 2;
+const BANK_BONUS: usize = 3;
+
+/// raised platforms left and right of the spot the ball drops from,
+/// below the rings and the hoop
+const PLATFORMS: [Rect; 2] = [
+    Rect {
+        min: Pos2::new(0.22, 0.68),
+        max: Pos2::new(0.36, 0.71),
+    },
+    Rect {
+        min: Pos2::new(0.6, 0.76),
+        max: Pos2::new(0.74, 0.79),
+    },
+];
+
+/// balls in play besides the main one in multi-ball mode
+const EXTRA_BALLS: usize = 2;
+
+/// most positions recorded for a single shot
+const MAX_SHOT_PATH: usize = 600;
+
+/// centers of the rings between the launch point and the hoop
+const RING_POSITIONS: [Pos2; 3] = [
+    Pos2::new(0.4, 0.4),
+    Pos2::new(0.55, 0.25),
+    Pos2::new(0.7, 0.35),
+];
+const RING_RADIUS: f32 = 0.06;
+const RING_BONUS: usize = 1;
+/// speed factor applied when the ball passes a ring
+const RING_BOOST: f32 = 1.15;
+
+/// spin added by a moving pad on contact
+const SPIN_KICK: f32 = 6.0;
+/// horizontal speed gained per unit of spin on a pad bounce
+const SPIN_GRIP: f32 = 0.02;
+/// sideways curve per unit of spin and vertical speed
+const MAGNUS: f32 = 0.02;
+/// fraction of spin lost per second
+const SPIN_DECAY: f32 = 0.5;
+
+const SLOW_MOTION_TIME: f32 = 0.6;
+const SLOW_MOTION_SCALE: f32 = 0.25;
+
+const MAX_WIND: f32 = 0.4;
+/// time in s between wind changes
+const WIND_INTERVAL: f32 = 4.0;
+const STREAKS: usize = 30;
+/// streak speed in court widths per second per unit of wind acceleration
+const STREAK_SPEED: f32 = 2.0;
+/// length of a streak at full wind strength
+const STREAK_LENGTH: f32 = 0.06;
+
+/// time in s to fill the power meter
+const CHARGE_TIME: f32 = 1.0;
+/// extra upward speed of a fully charged launch
+const LAUNCH_BOOST: f32 = 1.0;
+
+/// base of the cannon barrel
+const CANNON_POS: Pos2 = Pos2::new(0.08, 0.86);
+const CANNON_LENGTH: f32 = 0.08;
+const CANNON_START_ANGLE: f32 = std::f32::consts::FRAC_PI_4;
+const CANNON_MAX_ANGLE: f32 = 1.4;
+/// aiming speed in rad/s
+const CANNON_TURN_SPEED: f32 = 1.0;
+/// shot speed without and with a full charge
+const CANNON_MIN_SPEED: f32 = 0.8;
+const CANNON_MAX_SPEED: f32 = 2.0;
+
+/// simulated time step and number of steps for the trajectory preview
+const PREVIEW_STEP: f32 = 1. / 60.;
+const PREVIEW_STEPS: usize = 120;
 
 impl Game for Basketball {
-    fn new(_storage: Option<&dyn eframe::Storage>) -> Self {
-        Self {
+    fn new(storage: Option<&dyn eframe::Storage>) -> Self {
+        let highscore = storage
+            .and_then(|s| s.get_string("highscore"))
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(0);
+        let preset = storage
+            .and_then(|s| s.get_string("preset"))
+            .and_then(|s| Preset::from_name(&s))
+            .unwrap_or_default();
+        let skin = storage
+            .and_then(|s| s.get_string("skin"))
+            .and_then(|s| Skin::from_name(&s))
+            .unwrap_or_default();
+        let pad_speed = storage
+            .and_then(|s| s.get_string("pad_speed"))
+            .and_then(|s| PadSpeed::from_name(&s))
+            .unwrap_or_default();
+        let mut game = Self {
+            highscore,
+            start_highscore: highscore,
+            options: Options {
+                preset,
+                skin,
+                pad_speed,
+                ..Default::default()
+            },
             ball_pos: Pos2::new(0.5, 0.5),
             pad_pos: Pos2::new(0.3, 1. - FLOOR_HEIGHT),
+            cannon_angle: CANNON_START_ANGLE,
+            lives: LIVES,
+            time_left: CHALLENGE_TIME,
+            settings: Settings::load(storage),
+            achievements: Achievements::load(storage),
+            ghost: Ghost::load(storage),
             ..Default::default()
-        }
+        };
+        game.apply_options();
+        game
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        storage.set_string("highscore", self.highscore.to_string());
+        storage.set_string("preset", self.options.preset.name().to_string());
+        storage.set_string("skin", self.options.skin.name().to_string());
+        storage.set_string("pad_speed", self.options.pad_speed.name().to_string());
+        self.ghost.save(storage);
+        self.settings.save(storage);
+        self.achievements.save(storage);
     }
 
     fn draw(&mut self, ctx: &mut DrawContext, size: Vec2) {
@@ -39,78 +449,362 @@ impl Game for Basketball {
             egui::include_image!("../assets/background.png"),
             ImageFit::Cover,
         );
-        ctx.image_centered(
-            (self.pad_pos.x * size.x, self.pad_pos.y * size.y),
-            Vec2::new(BALL_SIZE * 4. * size.x, 0.),
-            egui::include_image!("../assets/trampolin.png"),
-        );
-        ctx.image_centered(
-            (self.ball_pos.x * size.x, self.ball_pos.y * size.y),
-            (BALL_SIZE * size.x, BALL_SIZE * size.y),
-            egui::include_image!("../assets/ball.png"),
-        );
-        ctx.image_anchor(
-            (HOOP_POS.x * size.x, HOOP_POS.y * size.y),
-            (HOOP_SIZE * size.x, HOOP_SIZE * size.y),
-            egui::include_image!("../assets/hoop.png"),
-            Anchor::TopCenter,
-        );
+        // the court keeps a fixed aspect, so positions and speeds in court
+        // fractions cover the same distances on every window shape
+        self.court_rect = ctx.sub_rect_margin(COURT_ASPECT, 0., None, |ctx, size| {
+            self.draw_court(ctx, size);
+            if self.debug {
+                self.draw_debug(ctx, size);
+            }
+            if let Some(time) = self.highlight {
+                self.draw_highlight(ctx, size, time);
+            }
+        });
+        let status = if self.options.timed {
+            format!("Time: {:.0}", self.time_left.ceil())
+        } else if self.options.versus {
+            self.versus.status()
+        } else {
+            format!("Lives: {}", self.lives)
+        };
         ctx.text(
             (10., 10.),
-            format!("Score: {}", self.score),
+            format!(
+                "Score: {}  Best: {}  {status}  Shots: {}",
+                self.score,
+                self.highscore,
+                self.accuracy_text()
+            ),
             30.,
-            Color32::BLACK,
+            self.settings.theme.text(),
         );
+        let hint = if self.options.cannon {
+            "Up/Down: aim the cannon, hold Space: charge a shot, H: help"
+        } else {
+            "Left/Right: move the pad, hold Space: charge a launch, H: help"
+        };
+        ctx.text_centered_anchor(
+            (size.x / 2., size.y - 30.),
+            hint,
+            20.,
+            self.settings.theme.text(),
+            Anchor::TopCenter,
+        );
+        if self.combo > 1 {
+            // grows with the combo up to a limit
+            let font_size = 30. + 5. * self.combo.min(6) as f32;
+            ctx.text(
+                (10., 50.),
+                format!("Combo x{}", self.combo),
+                font_size,
+                Color32::DARK_RED,
+            );
+        }
+        self.draw_power_meter(ctx, size);
+        if self.options.wind {
+            self.draw_wind_indicator(ctx, size);
+        }
 
-        if self.hit.is_some() {
-            ctx.rect_filled((0., 0.), size, Color32::from_black_alpha(200));
-            ctx.text_centered((size / 2.).to_pos2(), "You scored!", 50.0, Color32::WHITE);
+        // the overlay waits for the slow motion to end
+        if let Some(hit) = self.hit
+            && self.slow_motion <= 0.0
+        {
+            // fully faded out when the ball resets
+            let t = self.game_time - hit;
+            let fade = (t / HIT_FADE)
+                .min((HIT_DELAY - t) / HIT_FADE)
+                .clamp(0.0, 1.0) as f32;
+            ctx.rect_filled(
+                (0., 0.),
+                size,
+                Color32::from_black_alpha((200. * fade) as u8),
+            );
+            ctx.text_centered(
+                (size / 2.).to_pos2(),
+                format!("You scored! +{}", self.last_points),
+                50.0,
+                Color32::WHITE.gamma_multiply(fade),
+            );
+            let mut bonus_y = 60.;
+            if self.swish {
+                ctx.text_centered(
+                    (size / 2.).to_pos2() + Vec2::new(0., bonus_y),
+                    format!("Swish! +{SWISH_BONUS}"),
+                    40.0,
+                    Color32::GOLD.gamma_multiply(fade),
+                );
+                bonus_y += 50.;
+            }
+            if self.bank_shot {
+                ctx.text_centered(
+                    (size / 2.).to_pos2() + Vec2::new(0., bonus_y),
+                    format!("Bank shot! +{BANK_BONUS}"),
+                    40.0,
+                    Color32::LIGHT_BLUE.gamma_multiply(fade),
+                );
+            }
+        }
+        if self.game_over {
+            self.draw_game_over(ctx, size);
+        } else if self.paused {
+            ctx.rect_filled((0., 0.), size, Color32::from_black_alpha(150));
+            ctx.text_centered((size / 2.).to_pos2(), "Paused", 50.0, Color32::WHITE);
         }
+        if self.help {
+            self.draw_help(ctx, size);
+        }
+        self.achievements.draw(ctx, size);
+        self.settings.draw(ctx, size);
+        self.screenshot.draw(ctx, size);
     }
 
-    fn update(&mut self, ctx: &mut UpdateContext<Self>, delta: f32, size: Vec2) {
+    fn update(&mut self, ctx: &mut UpdateContext<Self>, delta: f32, _size: Vec2) {
+        self.screenshot.update(delta);
+        if self.screenshot.requested(ctx) {
+            match self.screenshot_canvas().encode() {
+                Ok(gif) => self.screenshot.save("basketball", gif),
+                Err(err) => log::warn!("failed to encode the screenshot: {err}"),
+            }
+        }
+        if self.settings.update(ctx) {
+            return;
+        }
+        self.achievements.update(delta);
+        // the challenge clock runs on real time, the game speed doesn't stretch it
+        let frame_delta = delta;
+        let delta = delta * self.settings.time_scale;
+        if ctx.key_pressed(Key::H) {
+            self.help = !self.help;
+        }
+        if self.help {
+            return;
+        }
+        if ctx.key_pressed(Key::F3) {
+            self.debug = !self.debug;
+        }
+        if ctx.key_pressed(Key::R) {
+            self.reset();
+            return;
+        }
+        if ctx.key_pressed(Key::C) {
+            self.options.timed = !self.options.timed;
+            self.options.versus = false;
+            self.reset();
+            return;
+        }
+        if ctx.key_pressed(Key::V) {
+            self.options.versus = !self.options.versus;
+            self.options.timed = false;
+            self.reset();
+            return;
+        }
+        if ctx.key_pressed(Key::L) {
+            self.options.cannon = !self.options.cannon;
+            self.reset();
+            return;
+        }
+        if self.game_over {
+            return;
+        }
+        if ctx.key_pressed(Key::P) {
+            self.paused = !self.paused;
+        }
+        // game time stands still, so the hit and miss timers freeze too
+        if self.paused {
+            return;
+        }
+        if ctx.key_pressed(Key::Y) {
+            self.toggle_highlight();
+        }
+        let made_duration = self.made_duration();
+        if let Some(time) = &mut self.highlight {
+            // the replay runs at the slow motion speed, independent of the frame rate
+            *time += f64::from(delta * SLOW_MOTION_SCALE);
+            if *time >= made_duration {
+                self.highlight = None;
+            }
+            return;
+        }
+        if self.options.timed {
+            // counts real time, slow motion doesn't buy extra seconds
+            self.time_left -= frame_delta;
+            if self.time_left <= 0.0 {
+                self.time_left = 0.0;
+                self.game_over = true;
+                return;
+            }
+        }
+        let delta = if self.slow_motion > 0.0 {
+            self.slow_motion -= delta;
+            delta * SLOW_MOTION_SCALE
+        } else {
+            delta
+        };
+        self.game_time += f64::from(delta);
+        if ctx.key_pressed(Key::M) {
+            self.options.moving_hoop = !self.options.moving_hoop;
+            self.apply_options();
+        }
+        if ctx.key_pressed(Key::T) {
+            self.options.multi_hoop = !self.options.multi_hoop;
+            self.apply_options();
+        }
+        if self.options.moving_hoop {
+            self.hoop_phase += delta * HOOP_SPEED * (1. + self.score as f32 * 0.1);
+            for (i, hoop) in self.hoops.iter_mut().enumerate() {
+                // offset the phase so the hoops don't move in sync
+                let phase = self.hoop_phase + i as f32;
+                hoop.pos.y = hoop.base.y + HOOP_AMPLITUDE * phase.sin();
+            }
+        }
+        if ctx.key_pressed(Key::D) {
+            self.options.pad_size = self.options.pad_size.next();
+            self.shrink_hoops();
+        }
+        if ctx.key_pressed(Key::G) {
+            self.options.preset = self.options.preset.next();
+            self.apply_options();
+        }
+        if ctx.key_pressed(Key::W) {
+            self.options.wind = !self.options.wind;
+            self.wind = 0.0;
+            self.wind_target = 0.0;
+        }
+        if ctx.key_pressed(Key::E) {
+            self.options.hide_streaks = !self.options.hide_streaks;
+        }
+        if ctx.key_pressed(Key::S) {
+            self.options.skin = self.options.skin.next();
+        }
+        if ctx.key_pressed(Key::F) {
+            self.options.pad_speed = self.options.pad_speed.next();
+        }
+        if ctx.key_pressed(Key::B) {
+            self.options.obstacle = !self.options.obstacle;
+        }
+        if ctx.key_pressed(Key::O) {
+            self.options.rings = !self.options.rings;
+            self.apply_options();
+        }
+        if ctx.key_pressed(Key::N) {
+            self.options.ghost = !self.options.ghost;
+        }
+        if ctx.key_pressed(Key::X) {
+            self.options.multi_ball = !self.options.multi_ball;
+            self.apply_options();
+        }
+        if ctx.key_pressed(Key::K) {
+            self.options.platforms = !self.options.platforms;
+            self.apply_options();
+        }
+        if self.options.wind {
+            self.update_wind(delta);
+            self.move_streaks(delta);
+        }
         if let Some(hit) = self.hit {
-            if ctx.time() - hit > HIT_DELAY {
+            if self.game_time - hit > HIT_DELAY {
                 self.hit = None;
-                self.ball_pos = Pos2::new(0.5, 0.5);
-                self.ball_speed = Vec2::new(0.0, 0.0);
+                self.reset_ball();
+                self.end_shot();
             }
         }
-
-        // update ball position
-        self.ball_pos += self.ball_speed * delta;
-        // update ball speed
-        if self.ball_pos.y < 1. - BALL_SIZE / 2. - FLOOR_HEIGHT {
-            self.ball_speed += Vec2::new(0.0, ACCELERATION * delta);
-        }
-        // collisions
-        if self.ball_pos.x - BALL_SIZE / 2. < 0.0 || self.ball_pos.x + BALL_SIZE / 2. > 1.0 {
-            self.ball_speed.x *= -ELASTICITY;
-            if self.ball_pos.x < 0.5 {
-                self.ball_pos.x = BALL_SIZE / 2.;
-            } else {
-                self.ball_pos.x = 1.0 - BALL_SIZE / 2.;
+        if let Some(since) = self.on_floor_since {
+            if self.game_time - since > MISS_DELAY {
+                if !self.options.timed && !self.options.versus {
+                    self.lives -= 1;
+                    self.game_over = self.lives == 0;
+                }
+                self.combo = 0;
+                self.reset_ball();
+                self.end_shot();
             }
         }
-        if self.ball_pos.y - BALL_SIZE / 2. < 0.0 {
-            self.ball_speed.y *= -ELASTICITY;
-            self.ball_pos.y = BALL_SIZE / 2.;
+        // the extra balls keep moving while the main one is held
+        self.update_extra_balls(delta);
+        if self.loaded {
+            self.handle_cannon(ctx, delta);
+            return;
         }
-        if self.ball_pos.y + BALL_SIZE / 2. > 1.0 - FLOOR_HEIGHT {
-            if self.ball_pos.x > self.pad_pos.x - BALL_SIZE * 2.
-                && self.ball_pos.x < self.pad_pos.x + BALL_SIZE * 2.
-            {
-                self.ball_speed.y = -self.ball_speed.y * ELASTICITY - 0.3;
+        if self.handle_aiming(ctx) {
+            // the ball is held while aiming
+            return;
+        }
+
+        // the wind leaves the ball alone until it was launched
+        if self.in_flight {
+            self.ball_speed.x += self.wind * delta;
+            // spin curves the flight sideways
+            self.ball_speed.x -= MAGNUS * self.spin * self.ball_speed.y * delta;
+        }
+        self.rotation += self.spin * delta;
+        self.spin *= (1. - SPIN_DECAY * delta).max(0.0);
+        let start = self.ball_pos;
+        match self.physics.move_ball(
+            &mut self.ball_pos,
+            &mut self.ball_speed,
+            self.pad_pos.x,
+            self.pad_width(),
+            &self.platforms,
+            delta,
+        ) {
+            Contact::Pad => {
+                // back on the pad without scoring, the last shot is over
+                if self.hit.is_none() && self.in_flight {
+                    self.in_flight = false;
+                    self.end_shot();
+                }
+                self.on_floor_since = None;
+                self.touched_wall = false;
+                self.shot_path.clear();
+                if self.pad_recharge() < 1.0 {
+                    // too soon after the last strong bounce, most of the kick is lost
+                    self.ball_speed.y += PAD_KICK * (1. - WEAK_KICK);
+                } else {
+                    self.last_strong_bounce = Some(self.game_time);
+                }
+                let charged = self.launch_power > 0.0;
+                self.ball_speed.y -= self.launch_power * LAUNCH_BOOST;
+                self.launch_power = 0.0;
+                // a released charge or a bounce up to the rims is a shot,
+                // lower dribbles are not
+                if self.hit.is_none() && (charged || self.reaches_rim()) {
+                    self.attempts += 1;
+                    self.in_flight = true;
+                }
+                // existing spin grips the pad, then the pad movement adds new spin
+                self.ball_speed.x += self.spin * SPIN_GRIP;
                 if ctx.key_down(Key::ArrowLeft) {
                     self.ball_speed.x -= 0.3;
+                    self.spin -= SPIN_KICK;
                 } else if ctx.key_down(Key::ArrowRight) {
                     self.ball_speed.x += 0.3;
+                    self.spin += SPIN_KICK;
                 }
-            } else {
-                self.ball_speed.y = -self.ball_speed.y * ELASTICITY;
             }
-            self.ball_pos.y = 1. - BALL_SIZE / 2. - FLOOR_HEIGHT;
+            // dropping the ball ends the combo
+            Contact::Floor if self.hit.is_none() => {
+                self.combo = 0;
+                self.on_floor_since.get_or_insert(self.game_time);
+            }
+            // bounces off a platform are part of the shot, only a ball
+            // that came to rest up there can't be played anymore
+            Contact::Platform if self.hit.is_none() && self.ball_speed == Vec2::ZERO => {
+                self.combo = 0;
+                self.on_floor_since.get_or_insert(self.game_time);
+            }
+            Contact::Wall => self.touched_wall = true,
+            _ => {}
+        }
+        if self.options.obstacle {
+            self.bounce_off_obstacle(start);
+        }
+        if self.hit.is_none() {
+            self.pass_rings(start);
+            if self.shot_path.len() < MAX_SHOT_PATH {
+                self.shot_path.push((self.game_time, self.ball_pos));
+            }
         }
+        self.debug_path = None;
         if self.hit.is_none() {
             // scoring
             // only check when ball is falling
@@ -122,36 +816,1112 @@ impl Game for Basketball {
                     ),
                     (self.ball_pos.x, self.ball_pos.y),
                 );
-                let rim_path = Line::new(
-                    (HOOP_POS.x - HOOP_SIZE / 2., HOOP_POS.y),
-                    (HOOP_POS.x + HOOP_SIZE / 2., HOOP_POS.y),
-                );
+                self.debug_path = Some(ball_path);
+                let scored = self
+                    .hoops
+                    .iter()
+                    .position(|hoop| ball_path.intersects(&hoop.rim()));
 
-                if ball_path.intersects(&rim_path) {
-                    self.hit = Some(ctx.time());
+                if let Some(index) = scored {
+                    let hoop = &self.hoops[index];
+                    self.scored_hoop = index;
+                    // where the ball crossed the rim height
+                    let t =
+                        (hoop.pos.y - ball_path.start.y) / (ball_path.end.y - ball_path.start.y);
+                    let cross_x = ball_path.start.x + t * (ball_path.end.x - ball_path.start.x);
+                    self.swish = (cross_x - hoop.pos.x).abs() < hoop.size / 2. * SWISH_ZONE;
+                    self.combo += 1;
+                    self.best_combo = self.best_combo.max(self.combo);
+                    self.last_points = hoop.points * self.combo;
+                    if self.swish {
+                        self.last_points += SWISH_BONUS;
+                    }
+                    self.bank_shot = self.touched_wall;
+                    self.touched_wall = false;
+                    if self.bank_shot {
+                        self.last_points += BANK_BONUS;
+                    }
+                    let path: Vec<Pos2> = self.shot_path.iter().map(|&(_, pos)| pos).collect();
+                    self.ghost.offer(self.last_points, &path);
+                    self.last_made.clone_from(&self.shot_path);
+                    self.score += self.last_points;
+                    self.versus.scores[self.versus.turn] += self.last_points;
+                    self.shrink_hoops();
+                    self.hit = Some(self.game_time);
+                    self.made += 1;
+                    // a moving rim can catch a bounce that wasn't counted as a shot
+                    if !self.in_flight {
+                        self.attempts += 1;
+                    }
+                    self.slow_motion = SLOW_MOTION_TIME;
+                    self.on_floor_since = None;
                     self.ball_speed.x *= 0.5;
-                    self.score += 1;
+                    self.highscore = self.score.max(self.highscore);
+                    self.grant_achievements();
                 }
             }
             // input
-            if ctx.key_down(Key::ArrowLeft) && self.pad_pos.x > 0.0 {
-                self.pad_pos.x -= 1.0 * delta;
+            if self.options.cannon {
+                // space only charges the cannon while it is loaded
+            } else if ctx.key_down(Key::Space) {
+                self.charge = (self.charge + delta / CHARGE_TIME).min(1.0);
+                self.charging = true;
+            } else if self.charging {
+                // released, launch on the next bounce
+                self.charging = false;
+                self.launch_power = self.charge;
+                self.charge = 0.0;
             }
-            if ctx.key_down(Key::ArrowRight) && self.pad_pos.x < 1.0 {
-                self.pad_pos.x += 1.0 * delta;
+            let speed = if ctx.key_down(SPRINT_KEY) {
+                self.options.pad_speed.speed() * SPRINT_FACTOR
+            } else {
+                self.options.pad_speed.speed()
+            };
+            if ctx.key_down(Key::ArrowLeft) {
+                self.pad_pos.x -= speed * delta;
             }
-        }
-        #[cfg(debug_assertions)]
-        if ctx.mouse_button_down(egui::PointerButton::Primary) {
-            let p = ctx.mouse_position();
-            self.ball_pos = Pos2::new(p.x / size.x, p.y / size.y);
-            self.ball_speed = Vec2::new(0.0, 0.0);
+            if ctx.key_down(Key::ArrowRight) {
+                self.pad_pos.x += speed * delta;
+            }
+            self.pad_pos.x = self.pad_pos.x.clamp(0.0, 1.0);
         }
     }
 
     fn reset(&mut self) {
-        // Reset your game state here
+        // clears score, pending hit and pad position
+        let highscore = self.highscore;
+        let ghost = std::mem::take(&mut self.ghost);
+        let options = self.options.clone();
+        let settings = std::mem::take(&mut self.settings);
+        let achievements = std::mem::take(&mut self.achievements);
+        let screenshot = std::mem::take(&mut self.screenshot);
         *self = Self::new(None);
+        self.highscore = highscore;
+        self.start_highscore = highscore;
+        self.ghost = ghost;
+        self.options = options;
+        self.settings = settings;
+        self.achievements = achievements;
+        self.screenshot = screenshot;
+        self.apply_options();
+        self.reset_ball();
+    }
+}
+
+/// two players shooting in turns
+#[derive(Clone, Default)]
+struct Match {
+    /// index of the player shooting now
+    turn: usize,
+    scores: [usize; 2],
+    shots: [usize; 2],
+}
+
+impl Match {
+    /// counts the shot and switches players, returns true once the match is over
+    fn next_turn(&mut self) -> bool {
+        self.shots[self.turn] += 1;
+        self.turn = 1 - self.turn;
+        self.shots.iter().all(|&shots| shots >= MATCH_SHOTS)
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "P{} to shoot  P1: {} ({}/{MATCH_SHOTS})  P2: {} ({}/{MATCH_SHOTS})",
+            self.turn + 1,
+            self.scores[0],
+            self.shots[0],
+            self.scores[1],
+            self.shots[1]
+        )
+    }
+
+    fn winner_text(&self) -> &'static str {
+        match self.scores[0].cmp(&self.scores[1]) {
+            std::cmp::Ordering::Greater => "Player 1 wins!\n Press R to restart",
+            std::cmp::Ordering::Less => "Player 2 wins!\n Press R to restart",
+            std::cmp::Ordering::Equal => "Draw!\n Press R to restart",
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Hoop {
+    /// rim center without movement
+    base: Pos2,
+    /// current rim center
+    pos: Pos2,
+    /// size without shrinking
+    base_size: f32,
+    /// current width, used for drawing and the rim
+    size: f32,
+    points: usize,
+}
+
+impl Hoop {
+    fn new(pos: Pos2, size: f32, points: usize) -> Self {
+        Self {
+            base: pos,
+            pos,
+            base_size: size,
+            size,
+            points,
+        }
+    }
+
+    fn rim(&self) -> Line<f32> {
+        Line::new(
+            (self.pos.x - self.size / 2., self.pos.y),
+            (self.pos.x + self.size / 2., self.pos.y),
+        )
+    }
+}
+
+/// Ball of the multi-ball mode without the spin and shot tracking of the main ball.
+///
+/// Balls don't collide with each other, they pass through one another.
+#[derive(Clone)]
+struct Ball {
+    pos: Pos2,
+    speed: Vec2,
+}
+
+impl Ball {
+    /// a ball dropping in at a random spot near the top
+    fn drop_in() -> Self {
+        Self {
+            pos: Pos2::new(random_u32(20..81) as f32 / 100., 0.2),
+            speed: Vec2::ZERO,
+        }
+    }
+}
+
+/// target the ball can fly through, checked along its vertical diameter
+#[derive(Clone)]
+struct Ring {
+    center: Pos2,
+    /// already passed during the current shot
+    passed: bool,
+}
+
+impl Ring {
+    fn new(center: Pos2) -> Self {
+        Self {
+            center,
+            passed: false,
+        }
+    }
+
+    fn line(&self) -> Line<f32> {
+        Line::new(
+            (self.center.x, self.center.y - RING_RADIUS),
+            (self.center.x, self.center.y + RING_RADIUS),
+        )
+    }
+}
+
+impl Basketball {
+    /// applies option changes to the derived state
+    fn apply_options(&mut self) {
+        self.extra_balls = if self.options.multi_ball {
+            (0..EXTRA_BALLS).map(|_| Ball::drop_in()).collect()
+        } else {
+            vec![]
+        };
+        self.platforms = if self.options.platforms {
+            PLATFORMS.to_vec()
+        } else {
+            vec![]
+        };
+        self.rings = if self.options.rings {
+            RING_POSITIONS.into_iter().map(Ring::new).collect()
+        } else {
+            vec![]
+        };
+        self.physics = self.options.preset.physics();
+        self.hoop_phase = 0.0;
+        self.hoops = vec![Hoop::new(HOOP_POS, HOOP_SIZE, 1)];
+        if self.options.multi_hoop {
+            // farther and smaller hoops are worth more
+            self.hoops
+                .push(Hoop::new(Pos2::new(0.55, 0.3), HOOP_SIZE * 0.8, 2));
+            self.hoops
+                .push(Hoop::new(Pos2::new(0.15, 0.35), HOOP_SIZE * 0.6, 3));
+        }
+        self.shrink_hoops();
+    }
+
+    /// hoops get smaller with the score, faster on higher difficulty
+    fn shrink_hoops(&mut self) {
+        let scale =
+            (1. - self.score as f32 * self.options.pad_size.hoop_shrink()).max(MIN_HOOP_SCALE);
+        for hoop in &mut self.hoops {
+            hoop.size = hoop.base_size * scale;
+        }
+    }
+
+    /// reflects the ball off the bar if it touched it on its way from `start`
+    fn bounce_off_obstacle(&mut self, start: Pos2) {
+        self.physics.bounce_off_bar(
+            (OBSTACLE_START, OBSTACLE_END),
+            start,
+            &mut self.ball_pos,
+            &mut self.ball_speed,
+        );
+    }
+
+    /// the cannon replaces the trampoline, so the ball never bounces off a pad
+    fn pad_width(&self) -> f32 {
+        if self.options.cannon {
+            0.0
+        } else {
+            self.options.pad_size.width()
+        }
+    }
+
+    /// moves the extra balls, they score like the main ball but without
+    /// combos or bonuses, and drop in again after scoring or coming to rest
+    fn update_extra_balls(&mut self, delta: f32) {
+        let physics = self.physics;
+        let (pad_x, pad_width) = (self.pad_pos.x, self.pad_width());
+        let mut points = 0;
+        for ball in &mut self.extra_balls {
+            let start = ball.pos;
+            ball.speed.x += self.wind * delta;
+            let contact = physics.move_ball(
+                &mut ball.pos,
+                &mut ball.speed,
+                pad_x,
+                pad_width,
+                &self.platforms,
+                delta,
+            );
+            let path = Line::new((start.x, start.y), (ball.pos.x, ball.pos.y));
+            let hoop = self
+                .hoops
+                .iter()
+                .find(|hoop| ball.speed.y > 0.0 && path.intersects(&hoop.rim()));
+            if let Some(hoop) = hoop {
+                points += hoop.points;
+                *ball = Ball::drop_in();
+            } else if matches!(contact, Contact::Floor | Contact::Platform)
+                && ball.speed == Vec2::ZERO
+            {
+                *ball = Ball::drop_in();
+            }
+        }
+        if points > 0 {
+            self.score += points;
+            self.versus.scores[self.versus.turn] += points;
+            self.highscore = self.score.max(self.highscore);
+            self.shrink_hoops();
+        }
+    }
+
+    /// awards the rings the ball passed on its path from `start`,
+    /// each ring counts once per shot and speeds the ball up
+    fn pass_rings(&mut self, start: Pos2) {
+        let ball_path = Line::new((start.x, start.y), (self.ball_pos.x, self.ball_pos.y));
+        for ring in &mut self.rings {
+            if ring.passed || !ball_path.intersects(&ring.line()) {
+                continue;
+            }
+            ring.passed = true;
+            self.score += RING_BONUS;
+            self.versus.scores[self.versus.turn] += RING_BONUS;
+            self.highscore = self.score.max(self.highscore);
+            self.ball_speed *= RING_BOOST;
+        }
+    }
+
+    /// how far the pad recharged since the last strong bounce, 0 to 1
+    fn pad_recharge(&self) -> f32 {
+        self.last_strong_bounce.map_or(1.0, |time| {
+            ((self.game_time - time) / PAD_RECHARGE).min(1.0) as f32
+        })
+    }
+
+    /// pad, ball, hoops and everything else placed in court fractions
+    fn draw_court(&self, ctx: &mut DrawContext, size: Vec2) {
+        ctx.image_centered(
+            (size / 2.).to_pos2(),
+            size,
+            egui::include_image!("../assets/background.png"),
+        );
+        self.draw_shadow(ctx, size);
+        if self.options.wind && !self.options.hide_streaks {
+            self.draw_streaks(ctx, size);
+        }
+        for platform in &self.platforms {
+            ctx.rect_filled(
+                (platform.min.x * size.x, platform.min.y * size.y),
+                (platform.width() * size.x, platform.height() * size.y),
+                Color32::from_rgb(120, 80, 40),
+            );
+        }
+        if self.options.cannon {
+            self.draw_cannon(ctx, size);
+        } else {
+            ctx.image_centered(
+                (self.pad_pos.x * size.x, self.pad_pos.y * size.y),
+                Vec2::new(self.pad_width() * size.x, 0.),
+                egui::include_image!("../assets/trampolin.png"),
+            );
+            self.draw_recharge(ctx, size);
+        }
+        // only while the next flight is about to start
+        if let Some(start) = self.aim_start {
+            self.draw_preview(ctx, size, launch_speed(start, self.aim_end), 0.0);
+        } else if self.loaded {
+            let speed = CANNON_MIN_SPEED + self.charge * (CANNON_MAX_SPEED - CANNON_MIN_SPEED);
+            self.draw_preview(ctx, size, self.cannon_direction() * speed, 0.0);
+        } else if self.launch_ready() {
+            let power = if self.charging {
+                self.charge
+            } else {
+                self.launch_power
+            };
+            self.draw_preview(ctx, size, self.ball_speed, power * LAUNCH_BOOST);
+        }
+        if self.options.ghost {
+            self.draw_ghost(ctx, size);
+        }
+        if self.aim_start.is_some() {
+            self.draw_aim_arrow(ctx, size);
+        }
+        ctx.image_centered(
+            (self.ball_pos.x * size.x, self.ball_pos.y * size.y),
+            (BALL_SIZE * size.x, BALL_SIZE * size.y),
+            self.options.skin.image(),
+        );
+        self.draw_spin_mark(ctx, size);
+        for ball in &self.extra_balls {
+            ctx.image_centered(
+                (ball.pos.x * size.x, ball.pos.y * size.y),
+                (BALL_SIZE * size.x, BALL_SIZE * size.y),
+                self.options.skin.image(),
+            );
+        }
+        for ring in &self.rings {
+            let color = if ring.passed {
+                Color32::GREEN
+            } else {
+                Color32::GOLD
+            };
+            arc(
+                ctx,
+                Pos2::new(ring.center.x * size.x, ring.center.y * size.y),
+                Vec2::new(size.x, size.y) * RING_RADIUS,
+                1.0,
+                3.,
+                color,
+            );
+        }
+        if self.options.obstacle {
+            ctx.line(
+                (OBSTACLE_START.x * size.x, OBSTACLE_START.y * size.y),
+                (OBSTACLE_END.x * size.x, OBSTACLE_END.y * size.y),
+                8.,
+                Color32::DARK_GRAY,
+            );
+        }
+        for (i, hoop) in self.hoops.iter().enumerate() {
+            // the net of the scored hoop wobbles until the overlay ends
+            let wobble = match self.hit {
+                Some(hit) if i == self.scored_hoop => (self.game_time - hit) as f32,
+                _ => HIT_DELAY as f32,
+            };
+            draw_net(ctx, hoop, wobble, size);
+            ctx.image_anchor(
+                (hoop.pos.x * size.x, hoop.pos.y * size.y),
+                (hoop.size * size.x, hoop.size * size.y),
+                egui::include_image!("../assets/hoop.png"),
+                Anchor::TopCenter,
+            );
+            if self.hoops.len() > 1 {
+                ctx.text_centered(
+                    (hoop.pos.x * size.x, hoop.pos.y * size.y - 20.),
+                    format!("+{}", hoop.points),
+                    20.,
+                    Color32::BLACK,
+                );
+            }
+        }
+    }
+
+    /// bar below the pad that fills up while it recharges
+    /// flat picture of the court for a screenshot, laid out like `draw_court`
+    fn screenshot_canvas(&self) -> Canvas {
+        let size = Vec2::new(SCREENSHOT_WIDTH, SCREENSHOT_WIDTH / COURT_ASPECT);
+        let to_screen = |p: Pos2| Pos2::new(p.x * size.x, p.y * size.y);
+        let to_rect = |r: Rect| Rect::from_min_max(to_screen(r.min), to_screen(r.max));
+        let mut canvas = Canvas::new(size, Color32::from_rgb(135, 190, 235));
+        canvas.rect_filled(to_rect(physics::FLOOR), Color32::from_rgb(190, 140, 90));
+        for platform in &self.platforms {
+            canvas.rect_filled(to_rect(*platform), Color32::from_rgb(120, 80, 40));
+        }
+        if self.options.cannon {
+            canvas.line(
+                to_screen(CANNON_POS),
+                to_screen(self.muzzle()),
+                BALL_SIZE * size.y * 0.8,
+                Color32::DARK_GRAY,
+            );
+            canvas.circle_filled(
+                to_screen(CANNON_POS),
+                BALL_SIZE * 0.6 * size.x,
+                Color32::BLACK,
+            );
+        } else {
+            let half = Vec2::new(self.pad_width() / 2., 0.0);
+            canvas.line(
+                to_screen(self.pad_pos - half),
+                to_screen(self.pad_pos + half),
+                6.,
+                Color32::from_rgb(40, 40, 120),
+            );
+        }
+        for ring in &self.rings {
+            let color = if ring.passed {
+                Color32::GREEN
+            } else {
+                Color32::GOLD
+            };
+            // the canvas has no arcs, so the ring is a closed chain of short lines
+            const SEGMENTS: usize = 32;
+            let point = |i: usize| {
+                let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+                to_screen(ring.center + Vec2::angled(angle) * RING_RADIUS)
+            };
+            for i in 0..SEGMENTS {
+                canvas.line(point(i), point(i + 1), 3., color);
+            }
+        }
+        if self.options.obstacle {
+            canvas.line(
+                to_screen(OBSTACLE_START),
+                to_screen(OBSTACLE_END),
+                8.,
+                Color32::DARK_GRAY,
+            );
+        }
+        for hoop in &self.hoops {
+            let half = Vec2::new(hoop.size / 2., 0.0);
+            canvas.line(
+                to_screen(hoop.pos - half),
+                to_screen(hoop.pos + half),
+                4.,
+                Color32::from_rgb(220, 60, 20),
+            );
+        }
+        let radius = BALL_SIZE / 2. * size.x;
+        for ball in &self.extra_balls {
+            canvas.circle_filled(to_screen(ball.pos), radius, Color32::from_rgb(230, 120, 30));
+        }
+        canvas.circle_filled(
+            to_screen(self.ball_pos),
+            radius,
+            Color32::from_rgb(230, 120, 30),
+        );
+        canvas
+    }
+
+    fn draw_recharge(&self, ctx: &mut DrawContext, size: Vec2) {
+        let recharge = self.pad_recharge();
+        let width = self.pad_width() * size.x;
+        let pos = Pos2::new(
+            self.pad_pos.x * size.x - width / 2.,
+            self.pad_pos.y * size.y + 15.,
+        );
+        ctx.rect_filled(pos, (width, 4.), Color32::from_black_alpha(100));
+        let color = if recharge < 1.0 {
+            Color32::ORANGE
+        } else {
+            Color32::GREEN
+        };
+        ctx.rect_filled(pos, (width * recharge, 4.), color);
+    }
+
+    fn cannon_direction(&self) -> Vec2 {
+        Vec2::angled(-self.cannon_angle)
+    }
+
+    fn muzzle(&self) -> Pos2 {
+        CANNON_POS + self.cannon_direction() * CANNON_LENGTH
+    }
+
+    /// Up/Down aims the loaded cannon, hold Space to charge and release to fire
+    fn handle_cannon(&mut self, ctx: &mut UpdateContext<'_, Self>, delta: f32) {
+        if ctx.key_down(Key::ArrowUp) {
+            self.cannon_angle += CANNON_TURN_SPEED * delta;
+        }
+        if ctx.key_down(Key::ArrowDown) {
+            self.cannon_angle -= CANNON_TURN_SPEED * delta;
+        }
+        self.cannon_angle = self.cannon_angle.clamp(0.0, CANNON_MAX_ANGLE);
+        self.ball_pos = self.muzzle();
+        if ctx.key_down(Key::Space) {
+            self.charge = (self.charge + delta / CHARGE_TIME).min(1.0);
+            self.charging = true;
+        } else if self.charging {
+            self.charging = false;
+            let speed = CANNON_MIN_SPEED + self.charge * (CANNON_MAX_SPEED - CANNON_MIN_SPEED);
+            self.ball_speed = self.cannon_direction() * speed;
+            self.charge = 0.0;
+            self.loaded = false;
+            self.touched_wall = false;
+            self.shot_path.clear();
+            self.attempts += 1;
+            self.in_flight = true;
+        }
+    }
+
+    /// drag away from the ball and release to launch it in the opposite direction,
+    /// returns true while the ball is held
+    fn handle_aiming(&mut self, ctx: &mut UpdateContext<'_, Self>) -> bool {
+        let p = ((ctx.mouse_position() - self.court_rect.min) / self.court_rect.size()).to_pos2();
+        if ctx.mouse_button_pressed(PointerButton::Primary)
+            && self.hit.is_none()
+            && (p - self.ball_pos).length() < BALL_SIZE * 1.5
+            && self.ball_speed.length() < GRAB_SPEED
+        {
+            self.aim_start = Some(p);
+            self.aim_end = p;
+        }
+        let Some(start) = self.aim_start else {
+            return false;
+        };
+        if ctx.mouse_button_down(PointerButton::Primary) {
+            self.aim_end = p;
+            return true;
+        }
+        // released, fire
+        self.ball_speed = launch_speed(start, self.aim_end);
+        self.touched_wall = false;
+        self.shot_path.clear();
+        self.attempts += 1;
+        self.in_flight = true;
+        self.aim_start = None;
+        false
+    }
+
+    /// barrel along the aim, with an aim line that grows with the charge
+    fn draw_cannon(&self, ctx: &mut DrawContext, size: Vec2) {
+        let to_screen = |p: Pos2| Pos2::new(p.x * size.x, p.y * size.y);
+        let muzzle = self.muzzle();
+        if self.loaded {
+            let reach = CANNON_LENGTH * (2. + 3. * self.charge);
+            ctx.line(
+                to_screen(muzzle),
+                to_screen(muzzle + self.cannon_direction() * reach),
+                2.,
+                Color32::DARK_RED,
+            );
+        }
+        ctx.line(
+            to_screen(CANNON_POS),
+            to_screen(muzzle),
+            BALL_SIZE * size.y * 0.8,
+            Color32::DARK_GRAY,
+        );
+        ellipse_filled(
+            ctx,
+            to_screen(CANNON_POS),
+            Vec2::new(size.x, size.y) * BALL_SIZE * 0.6,
+            Color32::BLACK,
+        );
+    }
+
+    /// shadow on the floor that shrinks and fades as the ball rises
+    fn draw_shadow(&self, ctx: &mut DrawContext, size: Vec2) {
+        let floor = 1. - FLOOR_HEIGHT;
+        let height = (floor - self.ball_pos.y).clamp(0.0, floor);
+        let scale = 1. - 0.7 * height / floor;
+        ellipse_filled(
+            ctx,
+            Pos2::new(self.ball_pos.x * size.x, floor * size.y),
+            Vec2::new(BALL_SIZE * size.x * 0.6, BALL_SIZE * size.y * 0.15) * scale,
+            Color32::from_black_alpha((120. * scale) as u8),
+        );
+    }
+
+    fn draw_help(&self, ctx: &mut DrawContext, size: Vec2) {
+        let controls = [
+            format!(
+                "Left/Right: move the pad, hold Z to sprint, F: pad speed ({})",
+                self.options.pad_speed.name()
+            ),
+            "the pad bounces weaker until the bar below it recharged".to_string(),
+            "hold Space: charge, the launch happens on the next bounce".to_string(),
+            "drag the slow ball: aim and throw it".to_string(),
+            "R: restart, P: pause, Esc: settings, F3: debug view, F2: screenshot".to_string(),
+            "M: moving hoop, T: more hoops, W: wind, B: bar, S: ball skin".to_string(),
+            "E: hide the wind streaks".to_string(),
+            "L: cannon instead of the trampoline, Up/Down aims it".to_string(),
+            "O: rings course, flying through a ring gives bonus points".to_string(),
+            "X: multi-ball, extra balls score their hoop points".to_string(),
+            "Y: replay the last score in slow motion".to_string(),
+            "K: platforms the ball bounces on like the floor".to_string(),
+            format!("N: ghost of the best shot ({} points)", self.ghost.points),
+            "C: 60s challenge, V: two players".to_string(),
+            format!(
+                "G: gravity ({}), D: difficulty ({})",
+                self.options.preset.name(),
+                self.options.pad_size.name()
+            ),
+        ]
+        .join("\n");
+        let scoring = format!(
+            "Drop the ball through a hoop from above to score.\n\
+             Consecutive scores without touching the floor multiply the points,\n\
+             a swish through the middle of the rim is worth +{SWISH_BONUS},\n\
+             banking the ball off a side wall first is worth +{BANK_BONUS}.\n\
+             A ball lying on the floor for too long costs a life."
+        );
+        let overlay = ctx.draw_centered(|ctx: &mut DrawContext<'_>| {
+            let rect = ctx
+                .text_centered_anchor(
+                    (size.x / 2.0, size.y / 4.0),
+                    "Help",
+                    30.,
+                    Color32::WHITE,
+                    Anchor::TopCenter,
+                )
+                .rect();
+            let rect = ctx
+                .text_centered_anchor(
+                    (size.x / 2.0, rect.max.y + 10.0),
+                    controls,
+                    20.,
+                    Color32::GREEN,
+                    Anchor::TopCenter,
+                )
+                .rect();
+            let rect = ctx
+                .text_centered_anchor(
+                    (size.x / 2.0, rect.max.y + 10.0),
+                    scoring,
+                    20.,
+                    Color32::GOLD,
+                    Anchor::TopCenter,
+                )
+                .rect();
+            ctx.text_centered_anchor(
+                (size.x / 2.0, rect.max.y + 10.0),
+                "H: close",
+                20.,
+                Color32::WHITE,
+                Anchor::TopCenter,
+            )
+            .rect();
+        });
+        overlay.background(10., Color32::from_black_alpha(200));
+    }
+
+    /// the scoring lines of the rims and the path checked against them
+    fn draw_debug(&self, ctx: &mut DrawContext, size: Vec2) {
+        let to_screen = |c: geo::Coord<f32>| Pos2::new(c.x * size.x, c.y * size.y);
+        for hoop in &self.hoops {
+            let rim = hoop.rim();
+            ctx.line(
+                to_screen(rim.start),
+                to_screen(rim.end),
+                2.,
+                Color32::YELLOW,
+            );
+        }
+        if let Some(path) = self.debug_path {
+            ctx.line(to_screen(path.start), to_screen(path.end), 3., Color32::RED);
+        }
+        ctx.text(
+            (10., 90.),
+            format!(
+                "ball ({:.3}, {:.3}) speed ({:.3}, {:.3})",
+                self.ball_pos.x, self.ball_pos.y, self.ball_speed.x, self.ball_speed.y
+            ),
+            16.,
+            Color32::RED,
+        );
+    }
+
+    /// line across the ball that turns with its rotation
+    fn draw_spin_mark(&self, ctx: &mut DrawContext, size: Vec2) {
+        if self.spin.abs() < 0.1 {
+            return;
+        }
+        let center = Pos2::new(self.ball_pos.x * size.x, self.ball_pos.y * size.y);
+        let offset = Vec2::angled(self.rotation) * BALL_SIZE * size.x * 0.4;
+        ctx.line(center - offset, center + offset, 2., Color32::BLACK);
+    }
+
+    fn draw_aim_arrow(&self, ctx: &mut DrawContext, size: Vec2) {
+        let Some(start) = self.aim_start else {
+            return;
+        };
+        let launch = launch_speed(start, self.aim_end) * 0.1;
+        if launch.length() < 0.001 {
+            return;
+        }
+        let from = Pos2::new(self.ball_pos.x * size.x, self.ball_pos.y * size.y);
+        let tip = from + Vec2::new(launch.x * size.x, launch.y * size.y);
+        ctx.line(from, tip, 4., Color32::DARK_RED);
+        let back = (from - tip).normalized() * 15.;
+        ctx.line(tip, tip + back + back.rot90() * 0.5, 4., Color32::DARK_RED);
+        ctx.line(tip, tip + back - back.rot90() * 0.5, 4., Color32::DARK_RED);
+    }
+
+    /// made and attempted shots with the hit rate
+    fn accuracy_text(&self) -> String {
+        if self.attempts == 0 {
+            return "0/0".to_string();
+        }
+        let percent = self.made * 100 / self.attempts;
+        format!("{}/{} ({percent}%)", self.made, self.attempts)
+    }
+
+    fn grant_achievements(&mut self) {
+        self.achievements.grant("first_basket", "First basket!");
+        if self.swish {
+            self.achievements.grant("swish", "Swish!");
+        }
+        if self.combo >= 5 {
+            self.achievements.grant("combo_5", "5-combo");
+        }
+        if self.score >= 50 {
+            self.achievements.grant("score_50", "Score 50");
+        }
+    }
+
+    fn reset_ball(&mut self) {
+        self.ball_pos = Pos2::new(0.5, 0.5);
+        self.ball_speed = Vec2::new(0.0, 0.0);
+        self.on_floor_since = None;
+        self.in_flight = false;
+        self.spin = 0.0;
+        self.touched_wall = false;
+        self.shot_path.clear();
+        for ring in &mut self.rings {
+            ring.passed = false;
+        }
+        if self.options.cannon {
+            self.loaded = true;
+            self.ball_pos = self.muzzle();
+        }
+    }
+
+    fn draw_game_over(&self, ctx: &mut DrawContext, size: Vec2) {
+        let title = if self.options.timed {
+            "Time's up!\n Press R to restart"
+        } else if self.options.versus {
+            self.versus.winner_text()
+        } else {
+            "Game Over!\n Press R to restart"
+        };
+        if self.options.versus {
+            let details = format!(
+                "Player 1: {}\nPlayer 2: {}",
+                self.versus.scores[0], self.versus.scores[1]
+            );
+            draw_game_over(ctx, size, title, &[(details, Color32::GREEN)]);
+            return;
+        }
+        // summary of the round
+        let mut lines = vec![
+            (
+                format!("Score: {}\nHighscore: {}", self.score, self.highscore),
+                Color32::GREEN,
+            ),
+            (
+                format!(
+                    "Best combo: x{}\nShots: {}",
+                    self.best_combo,
+                    self.accuracy_text()
+                ),
+                Color32::WHITE,
+            ),
+        ];
+        if self.score > self.start_highscore {
+            lines.push(("New high score!".to_string(), Color32::GOLD));
+        }
+        draw_game_over(ctx, size, title, &lines);
+    }
+
+    /// passes the ball to the other player in a match, the match ends
+    /// once both took all their shots
+    fn end_shot(&mut self) {
+        if !self.options.versus {
+            return;
+        }
+        self.combo = 0;
+        self.game_over = self.versus.next_turn();
+    }
+
+    /// the ball rises at least to the height of the lowest rim
+    fn reaches_rim(&self) -> bool {
+        let Some(rim) = self.hoops.iter().map(|hoop| hoop.pos.y).reduce(f32::max) else {
+            return false;
+        };
+        // height the upward speed carries the ball under gravity
+        let rise = self.ball_speed.y.min(0.0).powi(2) / (2. * self.physics.acceleration);
+        self.ball_pos.y - rise <= rim
+    }
+
+    /// a pad launch is charging or waiting for the next bounce
+    fn launch_ready(&self) -> bool {
+        self.hit.is_none() && !self.options.cannon && (self.charging || self.launch_power > 0.0)
+    }
+
+    fn update_wind(&mut self, delta: f32) {
+        self.wind_timer -= delta;
+        if self.wind_timer <= 0.0 {
+            self.wind_timer = WIND_INTERVAL;
+            self.wind_target = (random_u32(0..201) as f32 / 100. - 1.) * MAX_WIND;
+        }
+        // blend towards the target so the change is not abrupt
+        self.wind += (self.wind_target - self.wind) * delta.min(1.0);
+    }
+
+    /// drifts the streaks with the wind, those blown off one side come back
+    /// in at the other at a new height
+    fn move_streaks(&mut self, delta: f32) {
+        if self.streaks.is_empty() {
+            self.streaks = (0..STREAKS)
+                .map(|_| {
+                    Pos2::new(
+                        random_u32(0..101) as f32 / 100.,
+                        random_u32(0..91) as f32 / 100.,
+                    )
+                })
+                .collect();
+        }
+        for streak in &mut self.streaks {
+            streak.x += self.wind * STREAK_SPEED * delta;
+            if !(0.0..=1.0).contains(&streak.x) {
+                streak.x = streak.x.rem_euclid(1.0);
+                streak.y = random_u32(0..91) as f32 / 100.;
+            }
+        }
+    }
+
+    /// short lines trailing behind the streaks, longer and brighter in strong wind
+    fn draw_streaks(&self, ctx: &mut DrawContext, size: Vec2) {
+        let strength = self.wind / MAX_WIND;
+        if strength.abs() < 0.05 {
+            return;
+        }
+        let color = Color32::from_white_alpha((150. * strength.abs()) as u8);
+        for streak in &self.streaks {
+            let tail = *streak - Vec2::new(strength * STREAK_LENGTH, 0.);
+            ctx.line(
+                (streak.x * size.x, streak.y * size.y),
+                (tail.x * size.x, tail.y * size.y),
+                2.,
+                color,
+            );
+        }
+    }
+
+    /// arrow in the top right corner showing wind direction and strength
+    fn draw_wind_indicator(&self, ctx: &mut DrawContext, size: Vec2) {
+        let center = Pos2::new(size.x - 80., 30.);
+        let tip = center + Vec2::new(self.wind / MAX_WIND * 50., 0.);
+        ctx.text((center.x - 20., 50.), "Wind", 20., Color32::BLACK);
+        ctx.line(center, tip, 3., Color32::BLACK);
+        if self.wind.abs() > 0.01 {
+            let back = Vec2::new(-self.wind.signum() * 8., 0.);
+            ctx.line(tip, tip + back + Vec2::new(0., 6.), 3., Color32::BLACK);
+            ctx.line(tip, tip + back - Vec2::new(0., 6.), 3., Color32::BLACK);
+        }
+    }
+
+    fn draw_power_meter(&self, ctx: &mut DrawContext, size: Vec2) {
+        let power = if self.charging {
+            self.charge
+        } else {
+            self.launch_power
+        };
+        if power <= 0.0 {
+            return;
+        }
+        let (w, h) = (20., size.y * 0.3);
+        let (x, y) = (size.x - w - 10., size.y * 0.6 - h);
+        ctx.rect_filled((x, y), (w, h), Color32::from_black_alpha(100));
+        let color = if self.charging {
+            Color32::GOLD
+        } else {
+            Color32::RED
+        };
+        ctx.rect_filled((x, y + h * (1. - power)), (w, h * power), color);
+    }
+
+    /// replays the last scoring shot, a second press ends the replay early
+    fn toggle_highlight(&mut self) {
+        if self.highlight.is_some() {
+            self.highlight = None;
+        } else if self.hit.is_none() && self.last_made.len() > 1 {
+            self.highlight = Some(0.0);
+        }
+    }
+
+    /// length of the last scoring shot in s of game time
+    fn made_duration(&self) -> f64 {
+        match (self.last_made.first(), self.last_made.last()) {
+            (Some((start, _)), Some((end, _))) => end - start,
+            _ => 0.0,
+        }
+    }
+
+    /// the recorded ball over the dimmed court, `time` s into the shot
+    fn draw_highlight(&self, ctx: &mut DrawContext, size: Vec2, time: f64) {
+        ctx.rect_filled((0., 0.), size, Color32::from_black_alpha(150));
+        let start = self.last_made[0].0;
+        // first recorded position after `time`
+        let shown = self
+            .last_made
+            .partition_point(|(t, _)| t - start <= time)
+            .clamp(1, self.last_made.len() - 1);
+        for ((_, a), (_, b)) in self.last_made[..=shown]
+            .iter()
+            .zip(&self.last_made[1..=shown])
+        {
+            ctx.line(
+                (a.x * size.x, a.y * size.y),
+                (b.x * size.x, b.y * size.y),
+                3.,
+                Color32::from_rgba_unmultiplied(255, 215, 0, 120),
+            );
+        }
+        // between two recorded positions
+        let ((time_a, a), (time_b, b)) = (self.last_made[shown - 1], self.last_made[shown]);
+        let t = ((time + start - time_a) / (time_b - time_a).max(f64::EPSILON)).clamp(0.0, 1.0);
+        let pos = a.lerp(b, t as f32);
+        ctx.image_centered(
+            (pos.x * size.x, pos.y * size.y),
+            (BALL_SIZE * size.x, BALL_SIZE * size.y),
+            self.options.skin.image(),
+        );
+        ctx.text_centered_anchor(
+            (size.x / 2., 20.),
+            "Replay - Y: skip",
+            30.,
+            Color32::WHITE,
+            Anchor::TopCenter,
+        );
+    }
+
+    /// faint path of the best shot so far
+    fn draw_ghost(&self, ctx: &mut DrawContext, size: Vec2) {
+        for (a, b) in self.ghost.path.iter().zip(self.ghost.path.iter().skip(1)) {
+            ctx.line(
+                (a.x * size.x, a.y * size.y),
+                (b.x * size.x, b.y * size.y),
+                3.,
+                Color32::from_white_alpha(70),
+            );
+        }
+    }
+
+    /// dashed line along the predicted path of the ball starting with `speed`
+    fn draw_preview(&self, ctx: &mut DrawContext, size: Vec2, speed: Vec2, boost: f32) {
+        let mut pos = self.ball_pos;
+        let mut speed = speed;
+        let mut boost = boost;
+        let mut last = pos;
+        for i in 0..PREVIEW_STEPS {
+            let start = pos;
+            let contact = self.physics.move_ball(
+                &mut pos,
+                &mut speed,
+                self.pad_pos.x,
+                self.pad_width(),
+                &self.platforms,
+                PREVIEW_STEP,
+            );
+            // the charged launch happens on the next pad bounce
+            if contact == Contact::Pad {
+                speed.y -= boost;
+                boost = 0.0;
+            }
+            if self.options.obstacle {
+                self.physics.bounce_off_bar(
+                    (OBSTACLE_START, OBSTACLE_END),
+                    start,
+                    &mut pos,
+                    &mut speed,
+                );
+            }
+            // leave every other group of segments out to get dashes
+            if i / 4 % 2 == 0 {
+                ctx.line(
+                    (last.x * size.x, last.y * size.y),
+                    (pos.x * size.x, pos.y * size.y),
+                    2.,
+                    Color32::from_black_alpha(100),
+                );
+            }
+            last = pos;
+        }
+    }
+}
+
+/// procedural net below the rim, `t` is the time since the ball went through
+fn draw_net(ctx: &mut DrawContext, hoop: &Hoop, t: f32, size: Vec2) {
+    const STRANDS: usize = 5;
+    let fade = (1. - t / HIT_DELAY as f32).max(0.0);
+    let swing = hoop.size * 0.15 * fade * (t * 25.).sin();
+    let stretch = hoop.size * 0.2 * fade;
+    let bottom_y = hoop.pos.y + hoop.size * 0.6 + stretch;
+    let color = Color32::from_white_alpha(200);
+    let mut last_bottom = None;
+    for i in 0..STRANDS {
+        // -1..1 across the rim
+        let f = i as f32 / (STRANDS - 1) as f32 * 2. - 1.;
+        let top = Pos2::new(hoop.pos.x + f * hoop.size / 2., hoop.pos.y);
+        let bottom = Pos2::new(hoop.pos.x + f * hoop.size * 0.3 + swing, bottom_y);
+        ctx.line(
+            (top.x * size.x, top.y * size.y),
+            (bottom.x * size.x, bottom.y * size.y),
+            1.5,
+            color,
+        );
+        if let Some(last) = last_bottom {
+            ctx.line(
+                (last.x * size.x, last.y * size.y),
+                (bottom.x * size.x, bottom.y * size.y),
+                1.5,
+                color,
+            );
+        }
+        last_bottom = Some(bottom);
+    }
+}
+
+/// ellipse made of horizontal strips
+fn ellipse_filled(ctx: &mut DrawContext, center: Pos2, radius: Vec2, color: Color32) {
+    const STRIPS: usize = 8;
+    let strip_height = radius.y * 2. / STRIPS as f32;
+    for i in 0..STRIPS {
+        // vertical offset of the strip center in -1..1
+        let y = (i as f32 + 0.5) / STRIPS as f32 * 2. - 1.;
+        let half_width = radius.x * (1. - y * y).sqrt();
+        ctx.rect_filled(
+            (
+                center.x - half_width,
+                center.y + y * radius.y - strip_height / 2.,
+            ),
+            (half_width * 2., strip_height),
+            color,
+        );
+    }
+}
+
+/// slingshot speed for a drag from `start` to `end`
+fn launch_speed(start: Pos2, end: Pos2) -> Vec2 {
+    let speed = (start - end) * DRAG_POWER;
+    if speed.length() > MAX_LAUNCH_SPEED {
+        speed.normalized() * MAX_LAUNCH_SPEED
+    } else {
+        speed
     }
 }
 
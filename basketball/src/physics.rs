@@ -0,0 +1,201 @@
+use egui::{Pos2, Rect, Vec2};
+use geo::{Intersects, Line};
+
+use crate::{BALL_SIZE, FLOOR_HEIGHT};
+
+/// the main floor across the whole court, the only platform with the pad
+pub const FLOOR: Rect = Rect {
+    min: Pos2::new(0.0, 1.0 - FLOOR_HEIGHT),
+    max: Pos2::new(1.0, 1.0),
+};
+
+/// extra upward speed the pad gives on every bounce
+pub const PAD_KICK: f32 = 0.3;
+
+/// what the ball bounced off during a physics step
+#[derive(PartialEq, Eq)]
+pub enum Contact {
+    None,
+    Pad,
+    Floor,
+    /// the top of a raised platform
+    Platform,
+    /// one of the side walls
+    Wall,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Preset {
+    #[default]
+    Normal,
+    LowGravity,
+    Bouncy,
+}
+
+impl Preset {
+    pub fn physics(self) -> Physics {
+        match self {
+            Preset::Normal => Physics {
+                acceleration: 1.5,
+                elasticity: 0.9,
+                min_speed: 0.15,
+            },
+            Preset::LowGravity => Physics {
+                acceleration: 0.5,
+                elasticity: 0.9,
+                min_speed: 0.05,
+            },
+            Preset::Bouncy => Physics {
+                acceleration: 1.5,
+                elasticity: 1.0,
+                min_speed: 0.15,
+            },
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Preset::Normal => Preset::LowGravity,
+            Preset::LowGravity => Preset::Bouncy,
+            Preset::Bouncy => Preset::Normal,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Normal => "normal",
+            Preset::LowGravity => "low-g",
+            Preset::Bouncy => "bouncy",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Preset::Normal, Preset::LowGravity, Preset::Bouncy]
+            .into_iter()
+            .find(|p| p.name() == name)
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Physics {
+    pub acceleration: f32,
+    pub elasticity: f32,
+    pub min_speed: f32,
+}
+
+impl Physics {
+    /// advances the ball by one step and bounces it off walls, floor, pad
+    /// and the `platforms` besides the main floor
+    pub fn move_ball(
+        &self,
+        pos: &mut Pos2,
+        speed: &mut Vec2,
+        pad_x: f32,
+        pad_width: f32,
+        platforms: &[Rect],
+        delta: f32,
+    ) -> Contact {
+        let start = *pos;
+        // update ball position
+        *pos += *speed * delta;
+        // update ball speed
+        if pos.y < 1. - BALL_SIZE / 2. - FLOOR_HEIGHT {
+            *speed += Vec2::new(0.0, self.acceleration * delta);
+        }
+        // collisions
+        let mut contact = Contact::None;
+        if pos.x - BALL_SIZE / 2. < 0.0 || pos.x + BALL_SIZE / 2. > 1.0 {
+            contact = Contact::Wall;
+            speed.x *= -self.elasticity;
+            if pos.x < 0.5 {
+                pos.x = BALL_SIZE / 2.;
+            } else {
+                pos.x = 1.0 - BALL_SIZE / 2.;
+            }
+        }
+        if pos.y - BALL_SIZE / 2. < 0.0 {
+            speed.y *= -self.elasticity;
+            pos.y = BALL_SIZE / 2.;
+        }
+        // platforms are only solid from above and below, the ball passes their sides
+        for platform in std::iter::once(&FLOOR).chain(platforms) {
+            if pos.x < platform.min.x || pos.x > platform.max.x {
+                continue;
+            }
+            // nothing falls through the main floor, other platforms
+            // only catch the ball if it was above them before the step
+            let above = *platform == FLOOR || start.y + BALL_SIZE / 2. <= platform.min.y;
+            let landed = above && pos.y + BALL_SIZE / 2. > platform.min.y;
+            if !landed {
+                if start.y - BALL_SIZE / 2. >= platform.max.y
+                    && pos.y - BALL_SIZE / 2. < platform.max.y
+                {
+                    speed.y *= -self.elasticity;
+                    pos.y = platform.max.y + BALL_SIZE / 2.;
+                }
+                continue;
+            }
+            let on_pad = pos.x > pad_x - pad_width / 2. && pos.x < pad_x + pad_width / 2.;
+            if *platform == FLOOR && on_pad {
+                speed.y = -speed.y * self.elasticity - PAD_KICK;
+                contact = Contact::Pad;
+            } else {
+                speed.y = -speed.y * self.elasticity;
+                // too slow to bounce again, the ball comes to rest
+                // and gets reset once the miss delay runs out
+                if speed.y.abs() < self.min_speed {
+                    *speed = Vec2::ZERO;
+                }
+                contact = if *platform == FLOOR {
+                    Contact::Floor
+                } else {
+                    Contact::Platform
+                };
+            }
+            pos.y = platform.min.y - BALL_SIZE / 2.;
+        }
+        contact
+    }
+
+    /// bounces the ball off the bar from `a` to `b` after it moved from `start`,
+    /// the ball touches it anywhere along the bar or at its ends
+    pub fn bounce_off_bar(
+        &self,
+        (a, b): (Pos2, Pos2),
+        start: Pos2,
+        pos: &mut Pos2,
+        speed: &mut Vec2,
+    ) {
+        let path = Line::new((start.x, start.y), (pos.x, pos.y));
+        let crossed = path.intersects(&Line::new((a.x, a.y), (b.x, b.y)));
+        let closest = closest_point(a, b, *pos);
+        // a ball that went through the bar within the step goes back to the
+        // side it came from, otherwise it is pushed away from the closest point
+        let away = if crossed {
+            start - closest_point(a, b, start)
+        } else {
+            *pos - closest
+        };
+        if !crossed && away.length() >= BALL_SIZE / 2. {
+            return;
+        }
+        let normal = if away == Vec2::ZERO {
+            Vec2::new(0.0, -1.0)
+        } else {
+            away.normalized()
+        };
+        // only reflect while moving into the bar
+        let into = speed.dot(normal);
+        if into < 0.0 {
+            *speed -= normal * into * (1. + self.elasticity);
+        }
+        *pos = closest + normal * BALL_SIZE / 2.;
+    }
+}
+
+/// point on the segment from `a` to `b` closest to `p`
+fn closest_point(a: Pos2, b: Pos2, p: Pos2) -> Pos2 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0);
+    a + ab * t
+}
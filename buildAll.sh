@@ -4,8 +4,8 @@ set -e
 # Get the metadata for the workspace
 metadata=$(cargo metadata --format-version=1 --no-deps)
 
-# Extract the paths of the workspace members using jq
-member_paths=$(echo "$metadata" | jq -r '.packages[] | select(.source == null) | .manifest_path' | xargs -I {} dirname {})
+# Extract the paths of the workspace games (binary members) using jq
+member_paths=$(echo "$metadata" | jq -r '.packages[] | select(.source == null) | select(any(.targets[]; .kind | index("bin"))) | .manifest_path' | xargs -I {} dirname {})
 game_list=""
 
 rm -rf _site
@@ -0,0 +1,86 @@
+use egui::{Color32, Vec2};
+use egui_game::DrawContext;
+
+/// storage key of the unlocked achievement ids
+const STORAGE_KEY: &str = "achievements";
+/// time in s a toast stays on screen, including sliding in and out
+const TOAST_TIME: f32 = 3.0;
+const SLIDE_TIME: f32 = 0.3;
+const TOAST_WIDTH: f32 = 260.0;
+const TOAST_HEIGHT: f32 = 40.0;
+
+#[derive(Clone)]
+struct Toast {
+    title: String,
+    age: f32,
+}
+
+/// Unlocked achievements and the toasts announcing new ones.
+///
+/// Call [`Achievements::update`] every frame and draw them last with
+/// [`Achievements::draw`].
+#[derive(Clone, Default)]
+pub struct Achievements {
+    unlocked: Vec<String>,
+    toasts: Vec<Toast>,
+}
+
+impl Achievements {
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        let unlocked = storage
+            .and_then(|s| s.get_string(STORAGE_KEY))
+            .map(|text| text.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self {
+            unlocked,
+            toasts: Vec::new(),
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(STORAGE_KEY, self.unlocked.join("\n"));
+    }
+
+    /// unlocks an achievement, only the first grant of an id shows a toast
+    pub fn grant(&mut self, id: &str, title: &str) {
+        if self.unlocked.iter().any(|u| u == id) {
+            return;
+        }
+        self.unlocked.push(id.to_string());
+        self.toasts.push(Toast {
+            title: title.to_string(),
+            age: 0.0,
+        });
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        for toast in &mut self.toasts {
+            toast.age += delta;
+        }
+        self.toasts.retain(|t| t.age < TOAST_TIME);
+    }
+
+    /// stacks the toasts in the top right corner, sliding in from the edge
+    pub fn draw(&self, ctx: &mut DrawContext<'_>, size: Vec2) {
+        for (i, toast) in self.toasts.iter().enumerate() {
+            let shown = (toast.age / SLIDE_TIME)
+                .min((TOAST_TIME - toast.age) / SLIDE_TIME)
+                .clamp(0.0, 1.0);
+            // ease out so the toast slows down before it stops
+            let shown = 1.0 - (1.0 - shown) * (1.0 - shown);
+            let x = size.x - shown * (TOAST_WIDTH + 10.0);
+            let y = 10.0 + i as f32 * (TOAST_HEIGHT + 10.0);
+            ctx.rect_filled(
+                (x, y),
+                (TOAST_WIDTH, TOAST_HEIGHT),
+                Color32::from_black_alpha(200),
+            );
+            ctx.text(
+                (x + 10.0, y + 10.0),
+                format!("Achievement: {}", toast.title),
+                18.,
+                Color32::GOLD,
+            );
+        }
+    }
+}
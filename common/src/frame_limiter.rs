@@ -0,0 +1,53 @@
+use web_time::Instant;
+
+/// caps to cycle through, `None` is unlimited
+const CAPS: [Option<u32>; 4] = [None, Some(30), Some(60), Some(120)];
+
+/// Caps the frame rate by waiting out the rest of each frame.
+///
+/// egui_game repaints as fast as it can and doesn't expose eframe's
+/// repaint-after, so the game thread sleeps instead. Browsers already pace
+/// frames to the display, so nothing waits on the web.
+#[derive(Clone, Default)]
+pub struct FrameLimiter {
+    /// frames per second at most, unlimited if `None`
+    pub max_fps: Option<u32>,
+    last_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// call once per frame, sleeps until a frame at the cap has passed
+    pub fn wait(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(fps) = self.max_fps
+            && let Some(last) = self.last_frame
+        {
+            let frame = std::time::Duration::from_secs_f64(1.0 / f64::from(fps));
+            if let Some(rest) = frame.checked_sub(last.elapsed()) {
+                std::thread::sleep(rest);
+            }
+        }
+        self.last_frame = Some(Instant::now());
+    }
+
+    /// switches to the next cap, unlimited again after the highest
+    pub fn next(&mut self) {
+        let index = CAPS.iter().position(|&cap| cap == self.max_fps);
+        self.max_fps = CAPS[index.map_or(0, |i| (i + 1) % CAPS.len())];
+    }
+
+    pub fn name(&self) -> String {
+        match self.max_fps {
+            Some(fps) => fps.to_string(),
+            None => "unlimited".to_string(),
+        }
+    }
+
+    /// unlimited for anything that isn't a frame rate
+    pub fn from_name(name: &str) -> Self {
+        Self {
+            max_fps: name.parse().ok().filter(|&fps| fps > 0),
+            last_frame: None,
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use egui::{Color32, Vec2};
+use egui_game::{Anchor, DrawContext};
+
+const FONT_SIZE: f32 = 30.;
+const LINE_GAP: f32 = 10.;
+
+/// Centered game-over box: the title in white, then `lines` stacked below it
+/// in their own colors, all on a dimmed background.
+pub fn draw_game_over(
+    ctx: &mut DrawContext<'_>,
+    size: Vec2,
+    title: &str,
+    lines: &[(String, Color32)],
+) {
+    let screen = ctx.draw_centered(|ctx: &mut DrawContext<'_>| {
+        let mut rect = ctx
+            .text_centered_anchor(
+                (size.x / 2.0, size.y / 2.0),
+                title,
+                FONT_SIZE,
+                Color32::WHITE,
+                Anchor::TopCenter,
+            )
+            .rect();
+        for (text, color) in lines {
+            rect = ctx
+                .text_centered_anchor(
+                    (size.x / 2.0, rect.max.y + LINE_GAP),
+                    text.as_str(),
+                    FONT_SIZE,
+                    *color,
+                    Anchor::TopCenter,
+                )
+                .rect();
+        }
+    });
+    screen.background(10., Color32::from_black_alpha(200));
+}
@@ -0,0 +1,15 @@
+//! Pieces shared by all games.
+
+mod achievements;
+mod frame_limiter;
+mod game_over;
+mod screenshot;
+mod settings;
+mod shapes;
+
+pub use achievements::Achievements;
+pub use frame_limiter::FrameLimiter;
+pub use game_over::draw_game_over;
+pub use screenshot::{Canvas, Screenshot, write_file_from_bytes};
+pub use settings::{Settings, Theme};
+pub use shapes::{arc, triangle_filled};
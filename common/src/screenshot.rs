@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+
+use egui::{Color32, Key, Pos2, Rect, Vec2};
+use egui_game::utils::run_future;
+use egui_game::{Anchor, DrawContext, Game, UpdateContext};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// key that saves a screenshot in every game
+const SCREENSHOT_KEY: Key = Key::F2;
+/// time in s the confirmation stays on screen
+const SAVED_TIME: f32 = 1.5;
+/// most colors a gif frame can hold
+const MAX_COLORS: usize = 256;
+
+/// Picture of a game for a screenshot, exported as a gif.
+///
+/// The draw context can't read back what it painted, so a game fills a
+/// canvas with flat shapes in the same layout as its `draw`.
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    colors: Vec<Color32>,
+    /// index into `colors` for every pixel, row by row
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// canvas of `size` pixels filled with `background`
+    pub fn new(size: Vec2, background: Color32) -> Self {
+        let (width, height) = (size.x.max(1.) as usize, size.y.max(1.) as usize);
+        Self {
+            width,
+            height,
+            colors: vec![background.to_opaque()],
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32)
+    }
+
+    /// fills the pixels with their center inside `rect`, transparency is ignored
+    pub fn rect_filled(&mut self, rect: Rect, color: Color32) {
+        self.fill(rect, color, |_| true);
+    }
+
+    pub fn circle_filled(&mut self, center: Pos2, radius: f32, color: Color32) {
+        let rect = Rect::from_center_size(center, Vec2::splat(radius * 2.));
+        self.fill(rect, color, |p| p.distance_sq(center) <= radius * radius);
+    }
+
+    /// straight line with square caps
+    pub fn line(&mut self, a: Pos2, b: Pos2, width: f32, color: Color32) {
+        let rect = Rect::from_two_pos(a, b).expand(width / 2.);
+        let ab = b - a;
+        self.fill(rect, color, |p| {
+            let t = ((p - a).dot(ab) / ab.length_sq().max(f32::EPSILON)).clamp(0.0, 1.0);
+            p.distance(a + ab * t) <= width / 2.
+        });
+    }
+
+    /// sets the pixels in `rect` for which `inside` holds at their center
+    fn fill(&mut self, rect: Rect, color: Color32, inside: impl Fn(Pos2) -> bool) {
+        let index = self.color_index(color);
+        let x_range =
+            rect.min.x.max(0.) as usize..(rect.max.x.ceil().max(0.) as usize).min(self.width);
+        let y_range =
+            rect.min.y.max(0.) as usize..(rect.max.y.ceil().max(0.) as usize).min(self.height);
+        for y in y_range {
+            for x in x_range.clone() {
+                let center = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                if rect.contains(center) && inside(center) {
+                    self.pixels[y * self.width + x] = index;
+                }
+            }
+        }
+    }
+
+    /// palette index of `color`, once the palette is full the closest color is used
+    fn color_index(&mut self, color: Color32) -> u8 {
+        let color = color.to_opaque();
+        if let Some(index) = self.colors.iter().position(|c| *c == color) {
+            return index as u8;
+        }
+        if self.colors.len() < MAX_COLORS {
+            self.colors.push(color);
+            return (self.colors.len() - 1) as u8;
+        }
+        let distance = |c: &Color32| {
+            [(c.r(), color.r()), (c.g(), color.g()), (c.b(), color.b())]
+                .iter()
+                .map(|&(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+                .sum::<i32>()
+        };
+        (0..self.colors.len())
+            .min_by_key(|&i| distance(&self.colors[i]))
+            .unwrap_or(0) as u8
+    }
+
+    /// encodes the canvas as a single frame gif
+    pub fn encode(&self) -> Result<Vec<u8>, gif::EncodingError> {
+        let size_error = || gif::EncodingError::from(std::io::Error::other("canvas too large"));
+        let width = u16::try_from(self.width).map_err(|_| size_error())?;
+        let height = u16::try_from(self.height).map_err(|_| size_error())?;
+        let mut colors = self.colors.clone();
+        // gif palettes have a power of two length of at least two
+        colors.resize(colors.len().next_power_of_two().max(2), Color32::BLACK);
+        let palette: Vec<u8> = colors.iter().flat_map(|c| [c.r(), c.g(), c.b()]).collect();
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut bytes, width, height, &palette)?;
+            let frame = gif::Frame {
+                width,
+                height,
+                buffer: Cow::Borrowed(&self.pixels),
+                ..Default::default()
+            };
+            encoder.write_frame(&frame)?;
+        }
+        Ok(bytes)
+    }
+}
+
+/// Screenshot hotkey shared by the games, with a short note once it is saved.
+///
+/// Check [`Screenshot::requested`] in `Game::update` and pass the encoded
+/// picture to [`Screenshot::save`], draw the note last with [`Screenshot::draw`].
+#[derive(Clone, Default)]
+pub struct Screenshot {
+    /// time in s the note is still shown
+    saved: f32,
+}
+
+impl Screenshot {
+    /// the screenshot key was pressed, works whatever else the game does with keys
+    pub fn requested<G: Game>(&self, ctx: &mut UpdateContext<'_, G>) -> bool {
+        ctx.key_pressed(SCREENSHOT_KEY)
+    }
+
+    /// writes the gif to a file named after the game and the current time,
+    /// downloads it on the web
+    pub fn save(&mut self, game: &str, gif: Vec<u8>) {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+        run_future(write_file_from_bytes(format!("{game}-{seconds}.gif"), gif));
+        self.saved = SAVED_TIME;
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.saved = (self.saved - delta).max(0.0);
+    }
+
+    pub fn draw(&self, ctx: &mut DrawContext<'_>, size: Vec2) {
+        if self.saved <= 0.0 {
+            return;
+        }
+        let alpha = (self.saved / SAVED_TIME * 2.).min(1.0);
+        ctx.text_centered_anchor(
+            (size.x / 2., 10.),
+            "Screenshot saved",
+            20.,
+            Color32::WHITE.gamma_multiply(alpha),
+            Anchor::TopCenter,
+        );
+    }
+}
+
+/// lets the user pick a file and writes the bytes to it, downloads them on the web
+pub async fn write_file_from_bytes(name: String, bytes: Vec<u8>) {
+    let Some(file) = rfd::AsyncFileDialog::new()
+        .set_file_name(&name)
+        .save_file()
+        .await
+    else {
+        return;
+    };
+    if let Err(err) = file.write(&bytes).await {
+        log::warn!("failed to write {name}: {err}");
+    }
+}
@@ -0,0 +1,231 @@
+use egui::{Color32, Key, Vec2};
+use egui_game::{Anchor, DrawContext, Game, UpdateContext};
+
+use crate::FrameLimiter;
+
+/// storage key of the shared settings
+const STORAGE_KEY: &str = "settings";
+/// key that opens and closes the overlay
+const TOGGLE_KEY: Key = Key::Escape;
+const TIME_SCALE_STEP: f32 = 0.25;
+const MIN_TIME_SCALE: f32 = 0.25;
+const MAX_TIME_SCALE: f32 = 2.0;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// color behind the playing field
+    pub fn background(self) -> Color32 {
+        match self {
+            Self::Light => Color32::GRAY,
+            Self::Dark => Color32::from_gray(40),
+        }
+    }
+
+    /// color of text drawn straight on the game
+    pub fn text(self) -> Color32 {
+        match self {
+            Self::Light => Color32::BLACK,
+            Self::Dark => Color32::WHITE,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+}
+
+/// Settings overlay shared by the games, persisted in the app storage.
+///
+/// Call [`Settings::update`] first in `Game::update` and skip the game while
+/// it returns true, draw the overlay last with [`Settings::draw`].
+#[derive(Clone)]
+pub struct Settings {
+    /// factor applied to the frame time
+    pub time_scale: f32,
+    pub theme: Theme,
+    pub frame_limiter: FrameLimiter,
+    /// tells pickups and obstacles apart by shape, not only by color
+    pub shapes: bool,
+    /// whether the game draws shapes, hides the row otherwise
+    has_shapes: bool,
+    open: bool,
+    selected: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            time_scale: 1.0,
+            theme: Theme::default(),
+            frame_limiter: FrameLimiter::default(),
+            shapes: false,
+            has_shapes: false,
+            open: false,
+            selected: 0,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        let mut settings = Self::default();
+        let Some(text) = storage.and_then(|s| s.get_string(STORAGE_KEY)) else {
+            return settings;
+        };
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "time_scale" => settings.time_scale = value.parse().unwrap_or(settings.time_scale),
+                "theme" => {
+                    settings.theme = if value == "light" {
+                        Theme::Light
+                    } else {
+                        Theme::Dark
+                    }
+                }
+                "max_fps" => settings.frame_limiter = FrameLimiter::from_name(value),
+                "shapes" => settings.shapes = value == "true",
+                _ => {}
+            }
+        }
+        settings.time_scale = settings.time_scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+        settings
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        let text = format!(
+            "time_scale={}\ntheme={}\nmax_fps={}\nshapes={}",
+            self.time_scale,
+            self.theme.name(),
+            self.frame_limiter.name(),
+            self.shapes
+        );
+        storage.set_string(STORAGE_KEY, text);
+    }
+
+    /// shows the shapes row, for games that draw them
+    pub fn with_shapes(mut self) -> Self {
+        self.has_shapes = true;
+        self
+    }
+
+    /// rows of the overlay, selected with up and down
+    fn rows(&self) -> usize {
+        if self.has_shapes { 4 } else { 3 }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// handles the overlay input and the frame cap, returns true while the
+    /// game should stay paused
+    pub fn update<G: Game>(&mut self, ctx: &mut UpdateContext<'_, G>) -> bool {
+        self.frame_limiter.wait();
+        if ctx.key_pressed(TOGGLE_KEY) {
+            self.open = !self.open;
+            return true;
+        }
+        if !self.open {
+            return false;
+        }
+        let rows = self.rows();
+        if ctx.key_pressed(Key::ArrowUp) {
+            self.selected = (self.selected + rows - 1) % rows;
+        }
+        if ctx.key_pressed(Key::ArrowDown) {
+            self.selected = (self.selected + 1) % rows;
+        }
+        let change = if ctx.key_pressed(Key::ArrowRight) {
+            1.0
+        } else if ctx.key_pressed(Key::ArrowLeft) {
+            -1.0
+        } else {
+            return true;
+        };
+        match self.selected {
+            0 => {
+                self.time_scale = (self.time_scale + change * TIME_SCALE_STEP)
+                    .clamp(MIN_TIME_SCALE, MAX_TIME_SCALE);
+            }
+            1 => {
+                self.theme = match self.theme {
+                    Theme::Light => Theme::Dark,
+                    Theme::Dark => Theme::Light,
+                };
+            }
+            2 => self.frame_limiter.next(),
+            _ => self.shapes = !self.shapes,
+        }
+        true
+    }
+
+    pub fn draw(&self, ctx: &mut DrawContext<'_>, size: Vec2) {
+        if !self.open {
+            return;
+        }
+        let mut rows = vec![
+            format!("Speed: {:.2}x", self.time_scale),
+            format!("Theme: {}", self.theme.name()),
+            format!("FPS cap: {}", self.frame_limiter.name()),
+        ];
+        if self.has_shapes {
+            rows.push(format!(
+                "Shapes: {}",
+                if self.shapes { "on" } else { "off" }
+            ));
+        }
+        let text = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                if i == self.selected {
+                    format!("> {row} <")
+                } else {
+                    row.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let overlay = ctx.draw_centered(|ctx: &mut DrawContext<'_>| {
+            let rect = ctx
+                .text_centered_anchor(
+                    (size.x / 2.0, size.y / 3.0),
+                    "Settings",
+                    30.,
+                    Color32::WHITE,
+                    Anchor::TopCenter,
+                )
+                .rect();
+            let rect = ctx
+                .text_centered_anchor(
+                    (size.x / 2.0, rect.max.y + 10.0),
+                    text,
+                    25.,
+                    Color32::GREEN,
+                    Anchor::TopCenter,
+                )
+                .rect();
+            ctx.text_centered_anchor(
+                (size.x / 2.0, rect.max.y + 10.0),
+                "Up/Down: select, Left/Right: change, Esc: close",
+                20.,
+                Color32::WHITE,
+                Anchor::TopCenter,
+            )
+            .rect();
+        });
+        overlay.background(10., Color32::from_black_alpha(200));
+    }
+}
@@ -0,0 +1,45 @@
+use egui::{Color32, Pos2, Rect, Vec2};
+use egui_game::DrawContext;
+
+/// segments of a full turn, arcs use the matching share of them
+const SEGMENTS: usize = 32;
+
+/// elliptic arc made of line segments, clockwise from the top covering
+/// `fraction` of a full turn, a fraction of 1 draws the whole outline
+pub fn arc(
+    ctx: &mut DrawContext<'_>,
+    center: Pos2,
+    radius: Vec2,
+    fraction: f32,
+    width: f32,
+    color: Color32,
+) {
+    let point = |t: f32| {
+        let angle = t * std::f32::consts::TAU;
+        center + Vec2::new(angle.sin() * radius.x, -angle.cos() * radius.y)
+    };
+    let steps = (fraction * SEGMENTS as f32).ceil() as usize;
+    for i in 0..steps {
+        let from = i as f32 / SEGMENTS as f32;
+        let to = ((i + 1) as f32 / SEGMENTS as f32).min(fraction);
+        ctx.line(point(from), point(to), width, color);
+    }
+}
+
+/// triangle pointing up that fills `rect`, made of horizontal strips
+pub fn triangle_filled(ctx: &mut DrawContext<'_>, rect: Rect, color: Color32) {
+    const STRIPS: usize = 8;
+    let strip_height = rect.height() / STRIPS as f32;
+    for i in 0..STRIPS {
+        // width at the strip center, growing from the tip to the base
+        let width = rect.width() * (i as f32 + 0.5) / STRIPS as f32;
+        ctx.rect_filled(
+            (
+                rect.center().x - width / 2.,
+                rect.min.y + i as f32 * strip_height,
+            ),
+            (width, strip_height),
+            color,
+        );
+    }
+}
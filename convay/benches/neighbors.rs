@@ -0,0 +1,52 @@
+//! Compares the row-sum pass of `Stepper` with counting every cell on its own.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+// the step module only needs these from the game
+#[allow(dead_code)]
+#[path = "../src/step.rs"]
+mod step;
+
+use step::{Board, Stepper};
+
+const DEAD: u8 = 0;
+const ALIVE: u8 = 1;
+/// only the number of species matters to the step
+const SPECIES_COLORS: [(); 4] = [(); 4];
+
+const SIZE: usize = 500;
+
+fn random_cells() -> Vec<u8> {
+    let mut rng = fastrand::Rng::with_seed(1);
+    (0..SIZE * SIZE)
+        .map(|_| if rng.u32(0..100) < 25 { ALIVE } else { DEAD })
+        .collect()
+}
+
+fn neighbors(c: &mut Criterion) {
+    let cells = random_cells();
+    let board = Board {
+        cells: &cells,
+        cols: SIZE,
+        rows: SIZE,
+        colored: false,
+    };
+    let mut next = vec![DEAD; SIZE * SIZE];
+    let mut stepper = Stepper::default();
+    c.bench_function("step 500x500 row sums", |b| {
+        b.iter(|| stepper.step(board, &mut next));
+    });
+    c.bench_function("step 500x500 per cell", |b| {
+        b.iter(|| {
+            for row in 0..SIZE {
+                for col in 0..SIZE {
+                    let count = board.count_neighbors(col, row);
+                    next[row * SIZE + col] = board.apply_rules(col, row, count);
+                }
+            }
+        });
+    });
+}
+
+criterion_group!(benches, neighbors);
+criterion_main!(benches);
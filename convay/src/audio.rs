@@ -0,0 +1,90 @@
+use std::sync::mpsc::Sender;
+
+use web_time::{Duration, Instant};
+
+/// ticks closer together than this are dropped, fast step times would buzz
+const MIN_TICK_INTERVAL: Duration = Duration::from_millis(80);
+/// pitch of the tick in Hz
+const TICK_PITCH: f32 = 880.;
+const TICK_LENGTH: Duration = Duration::from_millis(25);
+/// kept soft, it plays on every step
+const TICK_VOLUME: f32 = 0.15;
+
+/// Plays a short tick on an audio thread.
+///
+/// The audio output can't move between threads, so the thread opens it and
+/// plays a tick for every message.
+pub struct Ticker {
+    ticks: Sender<()>,
+    last_tick: Option<Instant>,
+}
+
+impl Ticker {
+    /// starts the thread and opens the default output, fails without an
+    /// audio device and on the web
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn() -> std::io::Result<Self> {
+        use rodio::source::{SineWave, Source};
+        use std::sync::mpsc::channel;
+
+        let (ticks, tick_receiver) = channel::<()>();
+        let (ready_sender, ready) = channel();
+        std::thread::Builder::new()
+            .name("convay-audio".to_string())
+            .spawn(move || {
+                let output = rodio::OutputStream::try_default()
+                    .map_err(|err| err.to_string())
+                    .and_then(|(stream, handle)| {
+                        let sink = rodio::Sink::try_new(&handle).map_err(|err| err.to_string())?;
+                        Ok((stream, sink))
+                    });
+                // the stream stops playing once it is dropped
+                let (_stream, sink) = match output {
+                    Ok(output) => {
+                        let _ = ready_sender.send(Ok(()));
+                        output
+                    }
+                    Err(err) => {
+                        let _ = ready_sender.send(Err(err));
+                        return;
+                    }
+                };
+                // ends once the ticker and with it the sender is dropped
+                for () in tick_receiver {
+                    let tick = SineWave::new(TICK_PITCH)
+                        .take_duration(TICK_LENGTH)
+                        .amplify(TICK_VOLUME);
+                    sink.append(tick);
+                }
+            })?;
+        ready
+            .recv()
+            .map_err(std::io::Error::other)?
+            .map_err(std::io::Error::other)?;
+        Ok(Self {
+            ticks,
+            last_tick: None,
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawn() -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "no audio thread on the web",
+        ))
+    }
+
+    /// plays a tick unless the last one was too recent
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        if self
+            .last_tick
+            .is_some_and(|last| now - last < MIN_TICK_INTERVAL)
+        {
+            return;
+        }
+        self.last_tick = Some(now);
+        let _ = self.ticks.send(());
+    }
+}
@@ -5,13 +5,31 @@
     clippy::cast_possible_truncation
 )]
 
-use egui::{Color32, Key, PointerButton, Pos2, Rect, Vec2};
+mod audio;
+mod recording;
+mod snapshots;
+mod step;
+mod worker;
+
+use std::collections::VecDeque;
+use std::sync::LazyLock;
+
+use audio::Ticker;
+use common::{FrameLimiter, Screenshot, write_file_from_bytes};
+use egui::epaint::text::Fonts;
+use egui::{Color32, FontDefinitions, FontId, Key, PointerButton, Pos2, Rect, Vec2};
 use egui_game::EguiGame;
+use egui_game::utils::random_u32;
 use egui_game::{
     DrawContext, Game, UpdateContext,
     utils::{get_file_as_string, run_future, write_file_from_string},
 };
 use log::info;
+use recording::{MAX_FRAMES, Recording, Style};
+use snapshots::{SLOTS, Snapshot, Snapshots};
+use step::{Board, Stepper};
+use web_time::{Duration, Instant};
+use worker::{Job, Worker};
 
 enum GridMode {
     Lines,
@@ -19,14 +37,182 @@ enum GridMode {
     None,
 }
 
+/// colors of live cells and the background, species colors replace the live color
+#[derive(Clone, Copy, Default)]
+enum Palette {
+    #[default]
+    Classic,
+    Dark,
+    Amber,
+    Ocean,
+}
+
+impl Palette {
+    fn live(self) -> Color32 {
+        match self {
+            Palette::Classic => Color32::GREEN,
+            Palette::Dark => Color32::from_rgb(0, 255, 128),
+            Palette::Amber => Color32::GOLD,
+            Palette::Ocean => Color32::LIGHT_BLUE,
+        }
+    }
+
+    /// color of dead cells and the grid lines
+    fn background(self) -> Color32 {
+        match self {
+            Palette::Classic => Color32::WHITE,
+            Palette::Dark => Color32::from_gray(30),
+            Palette::Amber => Color32::BLACK,
+            Palette::Ocean => Color32::from_rgb(0, 20, 60),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Palette::Classic => Palette::Dark,
+            Palette::Dark => Palette::Amber,
+            Palette::Amber => Palette::Ocean,
+            Palette::Ocean => Palette::Classic,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Palette::Classic => "classic",
+            Palette::Dark => "dark",
+            Palette::Amber => "amber",
+            Palette::Ocean => "ocean",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Palette::Classic,
+            Palette::Dark,
+            Palette::Amber,
+            Palette::Ocean,
+        ]
+        .into_iter()
+        .find(|p| p.name() == name)
+    }
+}
+
+/// board that R restores, each target keeps its own copy
+#[derive(Clone, Copy, Default)]
+enum ResetTarget {
+    /// the glider the game starts with
+    Initial,
+    /// the board when drawing mode was last left
+    #[default]
+    Drawing,
+    /// the last loaded file, snapshot or random board
+    Loaded,
+}
+
+impl ResetTarget {
+    fn next(self) -> Self {
+        match self {
+            ResetTarget::Initial => ResetTarget::Drawing,
+            ResetTarget::Drawing => ResetTarget::Loaded,
+            ResetTarget::Loaded => ResetTarget::Initial,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ResetTarget::Initial => "initial glider",
+            ResetTarget::Drawing => "saved drawing",
+            ResetTarget::Loaded => "loaded board",
+        }
+    }
+}
+
+/// clickable controls above the grid, mirroring key actions
+#[derive(Clone, Copy)]
+enum Button {
+    PlayPause,
+    Step,
+    Reset,
+    Clear,
+    Randomize,
+}
+
+const BUTTONS: [Button; 5] = [
+    Button::PlayPause,
+    Button::Step,
+    Button::Reset,
+    Button::Clear,
+    Button::Randomize,
+];
+const BUTTON_SIZE: Vec2 = Vec2::new(110., 30.);
+
+const HUD_FONT_SIZE: f32 = 20.;
+/// texture size limit of the measuring fonts, their atlas is never uploaded
+const MEASURE_TEXTURE_SIDE: usize = 2048;
+
+/// the default fonts, used to measure the HUD before it is drawn
+static FONTS: LazyLock<Fonts> =
+    LazyLock::new(|| Fonts::new(1.0, MEASURE_TEXTURE_SIDE, FontDefinitions::default()));
+
 const START_SIZE: usize = 40;
+/// chance in percent that a cell is alive after randomizing
+const RANDOM_DENSITY: u32 = 25;
+/// cell state of a dead cell, live cells store their species starting at 1
+const DEAD: u8 = 0;
+/// the only species in classic mode
+const ALIVE: u8 = 1;
+/// colors of the species in colored mode, indexed by state - 1
+const SPECIES_COLORS: [Color32; 4] = [
+    Color32::GREEN,
+    Color32::from_rgb(0, 120, 255),
+    Color32::GOLD,
+    Color32::from_rgb(200, 0, 200),
+];
+const MAX_FAST_FORWARD: usize = 10_000;
+/// free cells around the pattern when fitting the grid to it
+const FIT_MARGIN: usize = 3;
+/// rows and columns added at each side when the infinite grid grows
+const GROW_MARGIN: usize = 5;
+/// the infinite grid stops growing at this size and clips again
+const MAX_INFINITE_SIZE: usize = 400;
+/// longest oscillator period that is detected
+const MAX_PERIOD: usize = 30;
+const MIN_BPM: f32 = 30.0;
+const MAX_BPM: f32 = 600.0;
+const BPM_STEP: f32 = 5.0;
+/// step durations kept for the timing graph
+const TIMING_SAMPLES: usize = 120;
+const TIMING_GRAPH_SIZE: Vec2 = Vec2::new(240., 80.);
+/// taps further apart than this in s start a new tap tempo
+const TAP_TIMEOUT: f32 = 2.0;
+/// taps averaged for the tempo
+const MAX_TAPS: usize = 8;
+/// keys of the snapshot slots, hold `SAVE_KEY` to save instead of load
+const SLOT_KEYS: [Key; SLOTS as usize] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+const SAVE_KEY: Key = Key::X;
+/// fraction of the activity kept per generation
+const ACTIVITY_DECAY: f32 = 0.9;
+/// activity drawn with the strongest heatmap color
+const ACTIVITY_FULL: f32 = 3.0;
 
 struct GameOfLife {
     rows: usize,
     cols: usize,
-    cells: Vec<bool>,
-    next_cells: Vec<bool>,
-    reset_cells: Vec<bool>,
+    cells: Vec<u8>,
+    next_cells: Vec<u8>,
+    /// boards R can restore, indexed by `ResetTarget`
+    reset_buffers: [Vec<u8>; 3],
+    reset_target: ResetTarget,
     step_time: f32,
     last_step_time: f32,
     time_elapsed: f32,
@@ -34,15 +220,72 @@ struct GameOfLife {
     grid_mode: GridMode,
     paused: bool,
     grid_rect: Rect,
+    /// cell under the mouse as (col, row)
+    hovered_cell: Option<(usize, usize)>,
+    /// keyboard editing position in drawing mode as (col, row)
+    cursor: (usize, usize),
+    generation: u64,
+    /// the last step didn't change any cell
+    stable: bool,
+    /// smallest number of steps after which the board repeats, 1 for still lifes
+    period: Option<usize>,
+    /// recent generations, oldest first, to detect oscillators
+    history: VecDeque<Vec<u8>>,
+    /// generations computed by one fast forward
+    fast_forward: usize,
+    /// width of the square brush in drawing mode
+    brush_size: usize,
+    /// pattern loaded from a file that clicks place instead of the brush,
+    /// as (col, row) offsets from its top left corner
+    stamp: Option<Vec<(usize, usize)>>,
+    /// generations captured for a gif export
+    recording: Option<Recording>,
+    /// newborn cells inherit the majority species of their parents
+    colored: bool,
+    /// species painted in colored drawing mode
+    brush_species: u8,
+    /// live cells are drawn as circles instead of squares
+    round_cells: bool,
+    snapshots: Snapshots,
+    palette: Palette,
+    /// beats per minute while the speed is set as a tempo, one step per beat
+    bpm: Option<f32>,
+    /// running time in s, used to measure tap tempo
+    clock: f32,
+    /// times of the recent tempo taps
+    taps: Vec<f32>,
+    /// screen area of the buttons, set while drawing
+    buttons: Vec<(Rect, Button)>,
+    /// decaying count of recent state changes per cell
+    activity: Vec<f32>,
+    heatmap: bool,
+    /// the grid grows when live cells reach its border
+    infinite: bool,
+    /// compute time of the recent steps in ms, oldest first
+    step_durations: VecDeque<f32>,
+    show_timing: bool,
+    /// seed of the random board, the same seed always gives the same board
+    seed: u64,
+    stepper: Stepper,
+    /// steps are computed on a background thread while this is set
+    worker: Option<Worker>,
+    /// plays a tick on every timed step while this is set
+    ticker: Option<Ticker>,
+    screenshot: Screenshot,
+    /// only the grid is drawn, without help and buttons
+    full_view: bool,
+    /// caps the frame rate, the board is idle between steps anyway
+    frame_limiter: FrameLimiter,
 }
 impl Default for GameOfLife {
     fn default() -> Self {
         let mut state = Self {
             rows: START_SIZE,
             cols: START_SIZE,
-            cells: vec![false; START_SIZE * START_SIZE],
-            next_cells: vec![false; START_SIZE * START_SIZE],
-            reset_cells: vec![],
+            cells: vec![DEAD; START_SIZE * START_SIZE],
+            next_cells: vec![DEAD; START_SIZE * START_SIZE],
+            reset_buffers: Default::default(),
+            reset_target: ResetTarget::default(),
             step_time: 0.5,
             last_step_time: 0.5,
             time_elapsed: 0.0,
@@ -50,44 +293,189 @@ impl Default for GameOfLife {
             grid_mode: GridMode::Lines,
             paused: false,
             grid_rect: Rect::ZERO,
+            hovered_cell: None,
+            cursor: (0, 0),
+            generation: 0,
+            stable: false,
+            period: None,
+            history: VecDeque::new(),
+            fast_forward: 100,
+            brush_size: 1,
+            stamp: None,
+            recording: None,
+            colored: false,
+            brush_species: ALIVE,
+            round_cells: false,
+            snapshots: Snapshots::default(),
+            palette: Palette::default(),
+            bpm: None,
+            clock: 0.0,
+            taps: vec![],
+            buttons: vec![],
+            activity: vec![0.0; START_SIZE * START_SIZE],
+            heatmap: false,
+            infinite: false,
+            step_durations: VecDeque::new(),
+            show_timing: false,
+            seed: 1,
+            stepper: Stepper::default(),
+            worker: None,
+            ticker: None,
+            screenshot: Screenshot::default(),
+            full_view: false,
+            frame_limiter: FrameLimiter::default(),
         };
         state.spawn_glider();
-        state.reset_cells = state.cells.clone();
+        state.reset_buffers = std::array::from_fn(|_| state.cells.clone());
         state
     }
 }
 
 impl Game for GameOfLife {
-    fn new(_storage: Option<&dyn eframe::Storage>) -> Self {
-        Self::default()
+    fn new(storage: Option<&dyn eframe::Storage>) -> Self {
+        let palette = storage
+            .and_then(|s| s.get_string("palette"))
+            .and_then(|s| Palette::from_name(&s))
+            .unwrap_or_default();
+        let frame_limiter = storage
+            .and_then(|s| s.get_string("max_fps"))
+            .map(|s| FrameLimiter::from_name(&s))
+            .unwrap_or_default();
+        Self {
+            snapshots: Snapshots::load(storage),
+            palette,
+            frame_limiter,
+            ..Self::default()
+        }
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.snapshots.save(storage);
+        storage.set_string("palette", self.palette.name().to_string());
+        storage.set_string("max_fps", self.frame_limiter.name());
     }
 
     fn update(&mut self, ctx: &mut UpdateContext<Self>, delta: f32, _size: Vec2) {
+        self.frame_limiter.wait();
+        self.clock += delta;
+        self.screenshot.update(delta);
+        if self.screenshot.requested(ctx) {
+            self.save_screenshot();
+        }
         self.handle_input(ctx);
+        self.poll_worker();
         self.time_elapsed += delta;
         if self.drawing_mode || self.time_elapsed < self.step_time || self.paused {
             return;
         }
         self.last_step_time = self.time_elapsed;
-        self.time_elapsed = 0.0;
-        self.update_cells();
+        self.time_elapsed = if self.bpm.is_some() {
+            // keep the overshoot so the steps stay on the beat
+            (self.time_elapsed - self.step_time).min(self.step_time)
+        } else {
+            0.0
+        };
+        if let Some(ticker) = &mut self.ticker {
+            ticker.tick();
+        }
+        if self.worker.is_some() {
+            self.update_cells_in_background();
+        } else {
+            self.update_cells();
+        }
     }
 
-    fn draw(&mut self, ctx: &mut DrawContext<'_>, _size: Vec2) {
-        let line_1 = "Space: draw, R: reset,  Up/Down: delay, Left/Right: size, G: grid mode";
+    fn draw(&mut self, ctx: &mut DrawContext<'_>, size: Vec2) {
+        let line_1 = format!(
+            "Space: draw, R: reset to {}, Tab: reset target, Up/Down: speed, M: delay/bpm, K: tap tempo, Left/Right: size, G: grid mode, L: colors, E: cell shape, A: heatmap, H/V: mirror, T: rotate, Z: center, Y: fit, J: invert, F2: screenshot, F11: full view",
+            self.reset_target.name()
+        );
         let line_2 = if self.drawing_mode {
-            "drawing mode. press Space to continue O: open file, S: save to file".to_string()
+            let cell = self
+                .hovered_cell
+                .map_or_else(|| "—".to_string(), |(x, y)| format!("({x}, {y})"));
+            let species = if self.colored {
+                format!(", N: species {}", self.brush_species)
+            } else {
+                String::new()
+            };
+            format!(
+                "drawing mode. press Space to continue, arrows/Enter: cursor, O: open file, I: open file as stamp, S: save to file, B: brush {0}x{0}{species}, cell: {cell}",
+                self.brush_size
+            )
         } else if self.paused {
             "Paused, P to continue, S to step".to_string()
+        } else if let Some(bpm) = self.bpm {
+            format!(
+                "Tempo: {bpm:.0} BPM, beat {:.2}s press P to pause and step",
+                self.step_time
+            )
         } else {
             format!(
                 "Delay Target: {:.1}s, Delay: {:.2}s press P to pause and step",
                 self.step_time, self.last_step_time
             )
         };
-        let text_rect = ctx
-            .text((5., 5.), format!("{line_1}\n{line_2}"), 20., Color32::WHITE)
-            .rect();
+        let record = self.recording.as_ref().map_or_else(
+            || "C: record gif".to_string(),
+            |r| {
+                format!(
+                    "recording {}/{MAX_FRAMES}, C: stop and save",
+                    r.frame_count()
+                )
+            },
+        );
+        let line_3 = format!(
+            "Generation: {}{}, F: skip {} generations, PageUp/PageDown: skip count, {record}, Q: {}, W: step thread {}, U: infinite grid {}, D: step timing, .: step sound {}, F4: fps cap {}",
+            self.generation,
+            match self.period {
+                Some(1) => " (still life, period 1)".to_string(),
+                Some(period) => format!(" (oscillator period {period})"),
+                None => String::new(),
+            },
+            self.fast_forward,
+            if self.colored {
+                "classic life"
+            } else {
+                "colored life"
+            },
+            if self.worker.is_some() { "on" } else { "off" },
+            if self.infinite { "on" } else { "off" },
+            if self.ticker.is_some() { "on" } else { "off" },
+            self.frame_limiter.name()
+        );
+        let filled: Vec<String> = self.snapshots.filled().map(|s| s.to_string()).collect();
+        let line_4 = format!(
+            "1-9: load snapshot, hold X and 1-9: save snapshot, saved: {}, [/]: random board, seed {}",
+            if filled.is_empty() {
+                "none".to_string()
+            } else {
+                filled.join(" ")
+            },
+            self.seed
+        );
+        // the full view leaves out the help and the buttons, the grid fills the window
+        let grid_top = if self.full_view {
+            self.buttons.clear();
+            0.0
+        } else {
+            // wrap the help so it fits narrow windows, the grid moves down with it,
+            // a new pass drops the measured lines that are not needed anymore
+            FONTS.begin_pass(1.0, MEASURE_TEXTURE_SIDE);
+            let text = [
+                line_1.as_str(),
+                line_2.as_str(),
+                line_3.as_str(),
+                line_4.as_str(),
+            ]
+            .map(|line| wrap(line, size.x - 10.))
+            .join("\n");
+            let text_rect = ctx
+                .text((5., 5.), text, HUD_FONT_SIZE, Color32::WHITE)
+                .rect();
+            self.draw_buttons(ctx, text_rect.max.y + 10.);
+            text_rect.max.y + BUTTON_SIZE.y + 20.
+        };
 
         let line_thickness = if matches!(self.grid_mode, GridMode::Lines) {
             2.0
@@ -95,7 +483,7 @@ impl Game for GameOfLife {
             0.0
         };
         self.grid_rect =
-            ctx.sub_square_margin(text_rect.max.y + 10., Some(Color32::WHITE), |ctx, size| {
+            ctx.sub_square_margin(grid_top, Some(self.palette.background()), |ctx, size| {
                 let (w, h) = (size.x, size.y);
                 let cw = w / self.cols as f32;
                 let ch = h / self.rows as f32;
@@ -104,76 +492,292 @@ impl Game for GameOfLife {
                 for row in 0..self.rows {
                     let y = row as f32 * ch;
                     if matches!(self.grid_mode, GridMode::Lines) && row > 0 {
-                        ctx.line((0.0, y), (w, y), line_thickness, Color32::WHITE);
+                        ctx.line((0.0, y), (w, y), line_thickness, self.palette.background());
                     }
                     for col in 0..self.cols {
                         let x = col as f32 * cw;
                         if matches!(self.grid_mode, GridMode::Lines) && col > 0 && row == 0 {
-                            ctx.line((x, 0.0), (x, h), line_thickness, Color32::WHITE);
+                            ctx.line((x, 0.0), (x, h), line_thickness, self.palette.background());
                         }
-                        let cell_color = if self.cells[self.get_index(col, row)] {
-                            Color32::GREEN
-                        } else if matches!(self.grid_mode, GridMode::Shaded) {
-                            if row % 2 == col % 2 {
-                                Color32::GRAY
-                            } else {
-                                Color32::DARK_GRAY
+                        let state = self.cells[self.get_index(col, row)];
+                        let shade = match self.grid_mode {
+                            GridMode::Shaded if row % 2 == col % 2 => Some(Color32::GRAY),
+                            GridMode::Shaded => Some(Color32::DARK_GRAY),
+                            _ => None,
+                        };
+                        let cell_pos = (x + offset, y + offset);
+                        let cell_size = (cw - offset * 2.0, ch - offset * 2.0);
+                        if state == DEAD {
+                            if let Some(shade) = shade {
+                                ctx.rect_filled(cell_pos, cell_size, shade);
                             }
                         } else {
-                            Color32::WHITE
-                        };
-                        if cell_color != Color32::WHITE {
-                            ctx.rect_filled(
-                                (x + offset, y + offset),
-                                (cw - offset * 2.0, ch - offset * 2.0),
-                                cell_color,
-                            );
+                            let cell_color = if self.colored {
+                                SPECIES_COLORS[usize::from(state - 1)]
+                            } else {
+                                self.palette.live()
+                            };
+                            if self.round_cells {
+                                // the shading stays visible around the circle
+                                if let Some(shade) = shade {
+                                    ctx.rect_filled(cell_pos, cell_size, shade);
+                                }
+                                circle_filled(ctx, cell_pos, cell_size, cell_color);
+                            } else {
+                                ctx.rect_filled(cell_pos, cell_size, cell_color);
+                            }
+                        }
+                        if self.heatmap {
+                            let heat = self.activity[self.get_index(col, row)] / ACTIVITY_FULL;
+                            if heat > 0.01 {
+                                let alpha = (heat.min(1.0) * 150.0) as u8;
+                                ctx.rect_filled(
+                                    cell_pos,
+                                    cell_size,
+                                    Color32::from_rgba_unmultiplied(255, 60, 0, alpha),
+                                );
+                            }
+                        }
+                        if self.drawing_mode
+                            && let (Some(stamp), Some((hx, hy))) = (&self.stamp, self.hovered_cell)
+                            && col >= hx
+                            && row >= hy
+                            && stamp.contains(&(col - hx, row - hy))
+                        {
+                            outline(ctx, cell_pos, cell_size, Color32::LIGHT_GREEN);
+                        }
+                        // drawn inside the cell, so it stays within the grid
+                        if self.hovered_cell == Some((col, row)) {
+                            outline(ctx, cell_pos, cell_size, Color32::WHITE);
+                        }
+                        if self.drawing_mode && self.cursor == (col, row) {
+                            outline(ctx, cell_pos, cell_size, Color32::GOLD);
+                        }
+                        if self.paused && !self.drawing_mode {
+                            // preview the next step
+                            let alive = state != DEAD;
+                            if alive != (self.next_state(col, row) != DEAD) {
+                                let color = if alive {
+                                    Color32::RED
+                                } else {
+                                    Color32::LIGHT_BLUE
+                                };
+                                outline(ctx, cell_pos, cell_size, color);
+                            }
                         }
                     }
                 }
             });
+        if self.show_timing {
+            self.draw_timing(ctx, size);
+        }
+        self.screenshot.draw(ctx, size);
     }
 
     fn reset(&mut self) {
-        self.cells.clone_from(&self.reset_cells);
+        self.cells
+            .clone_from(&self.reset_buffers[self.reset_target as usize]);
+        self.activity.fill(0.0);
         self.time_elapsed = 0.0;
+        self.restart_generations();
     }
 }
 
 impl GameOfLife {
     fn update_cells(&mut self) {
-        // Rules:
-        // A cell keeps its state if it has two neighbors.
-        // A cell becomes active if it has three neighbors.
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                let mut neighbors = 0;
-                for n_row in row.saturating_sub(1)..=(row + 1).min(self.rows - 1) {
-                    for n_col in col.saturating_sub(1)..=(col + 1).min(self.cols - 1) {
-                        // skip self
-                        if n_col == col && n_row == row {
-                            continue;
-                        }
-                        // check neighbor
-                        if self.cells[n_row * self.cols + n_col] {
-                            neighbors += 1;
-                        }
-                    }
-                }
-                // apply rules
-                if neighbors == 2 {
-                    // A cell keeps its state if it has two neighbors.
-                    self.next_cells[row * self.cols + col] = self.cells[row * self.cols + col];
-                } else if neighbors == 3 {
-                    // A cell becomes active if it has three neighbors.
-                    self.next_cells[row * self.cols + col] = true;
-                } else {
-                    self.next_cells[row * self.cols + col] = false;
-                }
-            }
+        let start = Instant::now();
+        let board = Board {
+            cells: &self.cells,
+            cols: self.cols,
+            rows: self.rows,
+            colored: self.colored,
+        };
+        self.stepper.step(board, &mut self.next_cells);
+        self.finish_step();
+        self.record_duration(start.elapsed());
+    }
+
+    fn record_duration(&mut self, duration: Duration) {
+        if self.step_durations.len() == TIMING_SAMPLES {
+            self.step_durations.pop_front();
+        }
+        self.step_durations
+            .push_back(duration.as_secs_f32() * 1000.);
+    }
+
+    /// hands the current board to the worker, unless it is still busy
+    /// with the last one, then this step is skipped
+    fn update_cells_in_background(&mut self) {
+        let Some(worker) = &mut self.worker else {
+            return;
+        };
+        if worker.busy() {
+            return;
+        }
+        worker.submit(Job {
+            cells: self.cells.clone(),
+            cols: self.cols,
+            rows: self.rows,
+            colored: self.colored,
+        });
+    }
+
+    /// takes over a step finished by the worker if the board didn't change
+    /// in the meantime, results for an edited or resized board are dropped
+    fn poll_worker(&mut self) {
+        let Some(done) = self.worker.as_mut().and_then(Worker::poll) else {
+            return;
+        };
+        if self.drawing_mode
+            || done.job.cols != self.cols
+            || done.job.colored != self.colored
+            || done.job.cells != self.cells
+        {
+            return;
+        }
+        self.next_cells = done.next;
+        self.finish_step();
+        self.record_duration(done.duration);
+    }
+
+    fn toggle_worker(&mut self) {
+        if self.worker.take().is_some() {
+            return;
+        }
+        match Worker::spawn() {
+            Ok(worker) => self.worker = Some(worker),
+            Err(err) => log::warn!("failed to start the step thread: {err}"),
+        }
+    }
+
+    fn toggle_ticker(&mut self) {
+        if self.ticker.take().is_some() {
+            return;
         }
+        match Ticker::spawn() {
+            Ok(ticker) => self.ticker = Some(ticker),
+            Err(err) => log::warn!("failed to start the step sound: {err}"),
+        }
+    }
+
+    /// everything after computing `next_cells`
+    fn finish_step(&mut self) {
         // swap cells
         std::mem::swap(&mut self.cells, &mut self.next_cells);
+        self.stable = self.cells == self.next_cells;
+        self.detect_period();
+        for ((activity, new), old) in self
+            .activity
+            .iter_mut()
+            .zip(&self.cells)
+            .zip(&self.next_cells)
+        {
+            *activity *= ACTIVITY_DECAY;
+            if (*new == DEAD) != (*old == DEAD) {
+                *activity += 1.0;
+            }
+        }
+        self.generation += 1;
+        if let Some(recording) = &mut self.recording
+            && !recording.push(&self.cells)
+        {
+            self.stop_recording();
+        }
+        // a recording needs the size to stay the same
+        if self.infinite && self.recording.is_none() {
+            self.grow_to_fit();
+        }
+    }
+
+    /// adds a margin around the grid when live cells touch its border,
+    /// the board stays in the middle
+    fn grow_to_fit(&mut self) {
+        let Some((c0, r0, c1, r1)) = self.live_bounds() else {
+            return;
+        };
+        let touches_border = c0 == 0 || r0 == 0 || c1 == self.cols - 1 || r1 == self.rows - 1;
+        let (rows, cols) = (
+            (self.rows + GROW_MARGIN * 2).min(MAX_INFINITE_SIZE),
+            (self.cols + GROW_MARGIN * 2).min(MAX_INFINITE_SIZE),
+        );
+        if !touches_border || (rows, cols) == (self.rows, self.cols) {
+            return;
+        }
+        let (d_row, d_col) = ((rows - self.rows) / 2, (cols - self.cols) / 2);
+        // resizing keeps the top left corner, then everything moves inwards
+        self.resize(rows, cols);
+        self.shift(d_col as isize, d_row as isize);
+    }
+
+    /// encodes the recorded generations and lets the user save the gif
+    fn stop_recording(&mut self) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+        match recording.encode(self.style(), self.palette, self.colored, self.step_time) {
+            Ok(bytes) => run_future(write_file_from_bytes("generations.gif".to_string(), bytes)),
+            Err(err) => log::warn!("failed to encode gif: {err}"),
+        }
+    }
+
+    /// saves the current board as a single frame gif in the recording style
+    fn save_screenshot(&mut self) {
+        let board = Recording::new(self.cols, self.rows, &self.cells);
+        match board.encode(self.style(), self.palette, self.colored, self.step_time) {
+            Ok(bytes) => self.screenshot.save("convay", bytes),
+            Err(err) => log::warn!("failed to encode the screenshot: {err}"),
+        }
+    }
+
+    /// how the grid mode looks in exported gifs
+    fn style(&self) -> Style {
+        match self.grid_mode {
+            GridMode::Lines => Style::Lines,
+            GridMode::Shaded => Style::Shaded,
+            GridMode::None => Style::Plain,
+        }
+    }
+
+    /// runs a batch of steps at once, stops early once the board is stable
+    fn skip_generations(&mut self, count: usize) {
+        for _ in 0..count {
+            self.update_cells();
+            if self.stable {
+                break;
+            }
+        }
+    }
+
+    /// state of a single cell after the next step, used for the preview
+    fn next_state(&self, col: usize, row: usize) -> u8 {
+        let board = self.board();
+        board.apply_rules(col, row, board.count_neighbors(col, row))
+    }
+
+    fn board(&self) -> Board<'_> {
+        Board {
+            cells: &self.cells,
+            cols: self.cols,
+            rows: self.rows,
+            colored: self.colored,
+        }
+    }
+
+    /// switches between classic and colored life,
+    /// live cells get a random species when colors are turned on
+    fn toggle_colored(&mut self) {
+        self.colored = !self.colored;
+        let species = SPECIES_COLORS.len() as u32;
+        for cell in &mut self.cells {
+            if *cell != DEAD {
+                *cell = if self.colored {
+                    random_u32(0..species) as u8 + 1
+                } else {
+                    ALIVE
+                };
+            }
+        }
+        self.brush_species = ALIVE;
     }
 
     fn spawn_glider(&mut self) {
@@ -183,7 +787,7 @@ impl GameOfLife {
             if idx >= self.cells.len() {
                 continue;
             }
-            self.cells[idx] = true;
+            self.cells[idx] = ALIVE;
         }
     }
 
@@ -191,39 +795,26 @@ impl GameOfLife {
         if rows < 1 || cols < 1 {
             return;
         }
-        std::mem::swap(&mut self.cells, &mut self.next_cells);
-        self.cells.resize(rows * cols, false);
-        self.cells.fill(false);
-        // map cells to new indices
-        for row in 0..rows {
-            for col in 0..cols {
-                if col >= self.cols || row >= self.rows {
-                    continue;
-                }
-                let old_index = row * self.cols + col;
-                let new_index = row * cols + col;
-                if new_index < self.cells.len() {
-                    self.cells[new_index] = self.next_cells[old_index];
+        // map cells to new indices, the reset buffers keep matching the grid
+        for cells in std::iter::once(&mut self.cells).chain(&mut self.reset_buffers) {
+            std::mem::swap(cells, &mut self.next_cells);
+            cells.resize(rows * cols, DEAD);
+            cells.fill(DEAD);
+            for row in 0..rows.min(self.rows) {
+                for col in 0..cols.min(self.cols) {
+                    cells[row * cols + col] = self.next_cells[row * self.cols + col];
                 }
             }
         }
-        std::mem::swap(&mut self.reset_cells, &mut self.next_cells);
-        self.reset_cells.resize(rows * cols, false);
-        self.reset_cells.fill(false);
-        // map cells to new indices
-        for row in 0..rows {
-            for col in 0..cols {
-                if col >= self.cols || row >= self.rows {
-                    continue;
-                }
-                let old_index = row * self.cols + col;
-                let new_index = row * cols + col;
-                if new_index < self.cells.len() {
-                    self.reset_cells[new_index] = self.next_cells[old_index];
-                }
+        self.next_cells.resize(rows * cols, DEAD);
+        self.cursor = (self.cursor.0.min(cols - 1), self.cursor.1.min(rows - 1));
+        let mut activity = vec![0.0; rows * cols];
+        for row in 0..rows.min(self.rows) {
+            for col in 0..cols.min(self.cols) {
+                activity[row * cols + col] = self.activity[row * self.cols + col];
             }
         }
-        self.next_cells.resize(rows * cols, false);
+        self.activity = activity;
         self.rows = rows;
         self.cols = cols;
     }
@@ -232,31 +823,398 @@ impl GameOfLife {
         y * self.cols + x
     }
 
+    /// moves every cell to the position returned by `map(rows, cols, col, row)`,
+    /// the reset buffers only change once the result is saved
+    fn transform(&mut self, map: impl Fn(usize, usize, usize, usize) -> (usize, usize)) {
+        self.next_cells.fill(DEAD);
+        // the heatmap moves along with the cells
+        let mut activity = vec![0.0; self.activity.len()];
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let (new_col, new_row) = map(self.rows, self.cols, col, row);
+                let (index, new_index) =
+                    (self.get_index(col, row), self.get_index(new_col, new_row));
+                self.next_cells[new_index] = self.cells[index];
+                activity[new_index] = self.activity[index];
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.next_cells);
+        self.activity = activity;
+    }
+
+    /// live cells span as (min col, min row, max col, max row)
+    fn live_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for (i, _) in self.cells.iter().enumerate().filter(|(_, c)| **c != DEAD) {
+            let (col, row) = (i % self.cols, i / self.cols);
+            bounds = Some(match bounds {
+                Some((c0, r0, c1, r1)) => (c0.min(col), r0.min(row), c1.max(col), r1.max(row)),
+                None => (col, row, col, row),
+            });
+        }
+        bounds
+    }
+
+    /// moves the board and the heatmap by the given offset, cells pushed over
+    /// the edge are dropped, the reset buffers keep their patterns in place
+    fn shift(&mut self, d_col: isize, d_row: isize) {
+        let mut activity = vec![0.0; self.activity.len()];
+        for (i, &value) in self.activity.iter().enumerate() {
+            let col = (i % self.cols) as isize + d_col;
+            let row = (i / self.cols) as isize + d_row;
+            if (0..self.cols as isize).contains(&col) && (0..self.rows as isize).contains(&row) {
+                activity[row as usize * self.cols + col as usize] = value;
+            }
+        }
+        self.activity = activity;
+        self.next_cells.fill(DEAD);
+        for (i, &cell) in self.cells.iter().enumerate().filter(|(_, c)| **c != DEAD) {
+            let col = (i % self.cols) as isize + d_col;
+            let row = (i / self.cols) as isize + d_row;
+            if (0..self.cols as isize).contains(&col) && (0..self.rows as isize).contains(&row) {
+                self.next_cells[row as usize * self.cols + col as usize] = cell;
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.next_cells);
+    }
+
+    /// moves the live cells to the middle of the grid
+    fn recenter(&mut self) {
+        let Some((c0, r0, c1, r1)) = self.live_bounds() else {
+            return;
+        };
+        let col = (self.cols - (c1 - c0 + 1)) / 2;
+        let row = (self.rows - (r1 - r0 + 1)) / 2;
+        self.shift(col as isize - c0 as isize, row as isize - r0 as isize);
+    }
+
+    /// resizes the grid to frame the live cells with a margin and centers them
+    fn fit_to_pattern(&mut self) {
+        let Some((c0, r0, c1, r1)) = self.live_bounds() else {
+            return;
+        };
+        // move to the top left first, resizing keeps that corner
+        self.shift(-(c0 as isize), -(r0 as isize));
+        let size = (c1 - c0).max(r1 - r0) + 1 + FIT_MARGIN * 2;
+        self.resize(size, size);
+        self.recenter();
+    }
+
+    /// sets a block of `brush_size` cells around (x, y) alive
+    fn paint(&mut self, x: usize, y: usize) {
+        let radius = self.brush_size / 2;
+        for row in y.saturating_sub(radius)..=y + radius {
+            for col in x.saturating_sub(radius)..=x + radius {
+                // clip at the grid edges
+                if col >= self.cols || row >= self.rows {
+                    continue;
+                }
+                let index = self.get_index(col, row);
+                self.cells[index] = self.brush_species;
+            }
+        }
+    }
+
+    /// grid cell at a screen position as (col, row)
+    fn cell_at(&self, pos: Pos2) -> Option<(usize, usize)> {
+        if !self.grid_rect.contains(pos) {
+            return None;
+        }
+        // convert to grid coordinates
+        let (x, y) = (pos.x - self.grid_rect.min.x, pos.y - self.grid_rect.min.y);
+        let cw = self.grid_rect.width() / self.cols as f32;
+        let ch = self.grid_rect.height() / self.rows as f32;
+        let col = ((x / cw).floor() as usize).min(self.cols - 1);
+        let row = ((y / ch).floor() as usize).min(self.rows - 1);
+        Some((col, row))
+    }
+
+    /// switches to tempo mode with the given beats per minute
+    fn set_bpm(&mut self, bpm: f32) {
+        let bpm = bpm.round().clamp(MIN_BPM, MAX_BPM);
+        self.bpm = Some(bpm);
+        self.step_time = 60.0 / bpm;
+    }
+
+    /// sets the tempo from the average interval of recent taps,
+    /// every tap also puts the next step on the beat
+    fn tap_tempo(&mut self) {
+        if self
+            .taps
+            .last()
+            .is_some_and(|&last| self.clock - last > TAP_TIMEOUT)
+        {
+            self.taps.clear();
+        }
+        self.taps.push(self.clock);
+        if self.taps.len() > MAX_TAPS {
+            self.taps.remove(0);
+        }
+        self.time_elapsed = 0.0;
+        if let [first, .., last] = self.taps[..] {
+            let interval = (last - first) / (self.taps.len() - 1) as f32;
+            self.set_bpm(60.0 / interval);
+        }
+    }
+
+    fn toggle_pause(&mut self) {
+        if !self.drawing_mode {
+            self.paused = !self.paused;
+        }
+    }
+
+    /// does a single step while paused
+    fn step(&mut self) {
+        if self.paused && !self.drawing_mode {
+            self.update_cells();
+        }
+    }
+
+    /// starts counting generations from the current board
+    fn restart_generations(&mut self) {
+        self.generation = 0;
+        self.stable = false;
+        self.period = None;
+        self.history.clear();
+    }
+
+    /// adds the previous generation to the history, then finds the board in it
+    fn detect_period(&mut self) {
+        // reuse the oldest buffer once the history is full
+        let mut entry = if self.history.len() >= MAX_PERIOD {
+            self.history.pop_front().unwrap_or_default()
+        } else {
+            vec![]
+        };
+        entry.clone_from(&self.next_cells);
+        self.history.push_back(entry);
+        self.period = self
+            .history
+            .iter()
+            .rev()
+            .position(|old| *old == self.cells)
+            .map(|i| i + 1);
+    }
+
+    /// flips every cell, dead cells come alive with the brush species.
+    /// only a drawing updates the reset buffer, inverting a running board
+    /// keeps the saved one
+    fn invert(&mut self) {
+        for cell in &mut self.cells {
+            *cell = if *cell == DEAD {
+                self.brush_species
+            } else {
+                DEAD
+            };
+        }
+        if self.drawing_mode {
+            self.keep_for_reset(ResetTarget::Drawing);
+        }
+        self.restart_generations();
+    }
+
+    fn clear(&mut self) {
+        self.cells.fill(DEAD);
+        self.restart_generations();
+    }
+
+    /// fills the board with random cells from the current seed,
+    /// random species in colored mode. it becomes the loaded board
+    fn randomize(&mut self) {
+        let species = if self.colored {
+            SPECIES_COLORS.len() as u8
+        } else {
+            1
+        };
+        let mut rng = fastrand::Rng::with_seed(self.seed);
+        for cell in &mut self.cells {
+            *cell = if rng.u32(0..100) < RANDOM_DENSITY {
+                rng.u8(0..species) + 1
+            } else {
+                DEAD
+            };
+        }
+        self.keep_for_reset(ResetTarget::Loaded);
+        self.restart_generations();
+    }
+
+    /// copies the board into the reset buffer of `target`,
+    /// the target R restores stays the one picked with Tab
+    fn keep_for_reset(&mut self, target: ResetTarget) {
+        self.reset_buffers[target as usize].clone_from(&self.cells);
+    }
+
+    fn press(&mut self, button: Button) {
+        match button {
+            Button::PlayPause => self.toggle_pause(),
+            Button::Step => self.step(),
+            Button::Reset => self.reset(),
+            Button::Clear => self.clear(),
+            Button::Randomize => {
+                self.seed = u64::from(random_u32(0..100_000));
+                self.randomize();
+            }
+        }
+    }
+
+    fn button_label(&self, button: Button) -> &'static str {
+        match button {
+            Button::PlayPause if self.paused => "Play",
+            Button::PlayPause => "Pause",
+            Button::Step => "Step",
+            Button::Reset => "Reset",
+            Button::Clear => "Clear",
+            Button::Randomize => "Randomize",
+        }
+    }
+
+    /// bar graph of the recent step durations in the bottom right corner
+    fn draw_timing(&self, ctx: &mut DrawContext<'_>, size: Vec2) {
+        let rect = Rect::from_min_size(
+            (size - TIMING_GRAPH_SIZE - Vec2::splat(10.)).to_pos2(),
+            TIMING_GRAPH_SIZE,
+        );
+        ctx.rect_filled(rect.min, rect.size(), Color32::from_black_alpha(180));
+        // scaled to the slowest step, but at least one ms high
+        let max = self.step_durations.iter().copied().fold(0.0, f32::max);
+        let scale = max.max(1.0);
+        let bar_width = rect.width() / TIMING_SAMPLES as f32;
+        for (i, ms) in self.step_durations.iter().enumerate() {
+            let height = ms / scale * rect.height();
+            ctx.rect_filled(
+                (rect.min.x + i as f32 * bar_width, rect.max.y - height),
+                (bar_width, height),
+                Color32::LIGHT_GREEN,
+            );
+        }
+        let last = self.step_durations.back().copied().unwrap_or(0.0);
+        ctx.text(
+            (rect.min.x, rect.min.y - HUD_FONT_SIZE - 5.),
+            format!(
+                "step {last:.2}ms, max {max:.2}ms, delay {:.2}s",
+                self.last_step_time
+            ),
+            HUD_FONT_SIZE * 0.8,
+            Color32::WHITE,
+        );
+    }
+
+    /// row of buttons starting at height `y`, remembers their areas for clicks
+    fn draw_buttons(&mut self, ctx: &mut DrawContext<'_>, y: f32) {
+        self.buttons.clear();
+        for (i, button) in BUTTONS.into_iter().enumerate() {
+            let pos = Pos2::new(5. + i as f32 * (BUTTON_SIZE.x + 10.), y);
+            // greyed out while the action does nothing
+            let enabled = match button {
+                Button::PlayPause => !self.drawing_mode,
+                Button::Step => self.paused && !self.drawing_mode,
+                _ => true,
+            };
+            let color = if enabled {
+                Color32::LIGHT_GRAY
+            } else {
+                Color32::DARK_GRAY
+            };
+            ctx.rect_filled(pos, BUTTON_SIZE, color);
+            ctx.text(
+                pos + Vec2::new(10., 5.),
+                self.button_label(button),
+                18.,
+                Color32::BLACK,
+            );
+            self.buttons
+                .push((Rect::from_min_size(pos, BUTTON_SIZE), button));
+        }
+    }
+
+    /// arrows change the speed and the grid size outside of drawing mode
+    fn handle_arrows(&mut self, ctx: &mut UpdateContext<'_, GameOfLife>) {
+        if let Some(bpm) = self.bpm {
+            // faster is up in beats, but down in seconds
+            if ctx.key_pressed(Key::ArrowUp) {
+                self.set_bpm(bpm + BPM_STEP);
+            }
+            if ctx.key_pressed(Key::ArrowDown) {
+                self.set_bpm(bpm - BPM_STEP);
+            }
+        } else {
+            if ctx.key_pressed(Key::ArrowUp) {
+                self.step_time = (self.step_time + 0.1).min(2.0);
+            }
+            if ctx.key_pressed(Key::ArrowDown) {
+                self.step_time = (self.step_time - 0.1).max(0.0);
+            }
+        }
+        if ctx.key_pressed(Key::ArrowLeft) {
+            self.resize(self.rows - 1, self.cols - 1);
+        }
+        if ctx.key_pressed(Key::ArrowRight) {
+            self.resize(self.rows + 1, self.cols + 1);
+        }
+    }
+
+    /// arrows move the keyboard cursor in drawing mode, Enter toggles its cell
+    fn handle_cursor(&mut self, ctx: &mut UpdateContext<'_, GameOfLife>) {
+        let (col, row) = self.cursor;
+        if ctx.key_pressed(Key::ArrowLeft) {
+            self.cursor.0 = col.saturating_sub(1);
+        }
+        if ctx.key_pressed(Key::ArrowRight) {
+            self.cursor.0 = (col + 1).min(self.cols - 1);
+        }
+        if ctx.key_pressed(Key::ArrowUp) {
+            self.cursor.1 = row.saturating_sub(1);
+        }
+        if ctx.key_pressed(Key::ArrowDown) {
+            self.cursor.1 = (row + 1).min(self.rows - 1);
+        }
+        if ctx.key_pressed(Key::Enter) {
+            self.toggle_cell(self.cursor.0, self.cursor.1);
+        }
+    }
+
+    /// flips a cell between dead and the brush species
+    fn toggle_cell(&mut self, x: usize, y: usize) {
+        let index = self.get_index(x, y);
+        self.cells[index] = if self.cells[index] == DEAD {
+            self.brush_species
+        } else {
+            DEAD
+        };
+    }
+
     fn handle_input(&mut self, ctx: &mut UpdateContext<'_, GameOfLife>) {
+        self.hovered_cell = self.cell_at(ctx.mouse_position());
         if ctx.key_pressed(Key::Space) {
             if self.drawing_mode {
                 // save drawing for reset
-                self.reset_cells.clone_from(&self.cells);
+                self.keep_for_reset(ResetTarget::Drawing);
                 info!("Saved drawing");
             } else {
-                self.cells.fill(false);
+                self.clear();
             }
             self.drawing_mode = !self.drawing_mode;
         }
         if ctx.key_pressed(Key::R) {
             self.reset();
         }
-        if ctx.key_pressed(Key::ArrowUp) {
-            self.step_time = (self.step_time + 0.1).min(2.0);
+        if ctx.key_pressed(Key::Tab) {
+            self.reset_target = self.reset_target.next();
         }
-        if ctx.key_pressed(Key::ArrowDown) {
-            self.step_time = (self.step_time - 0.1).max(0.0);
+        if self.drawing_mode {
+            self.handle_cursor(ctx);
+        } else {
+            self.handle_arrows(ctx);
         }
-        if ctx.key_pressed(Key::ArrowLeft) {
-            self.resize(self.rows - 1, self.cols - 1);
+        if ctx.key_pressed(Key::M) {
+            if self.bpm.is_some() {
+                self.bpm = None;
+            } else {
+                self.set_bpm(60.0 / self.step_time.max(0.1));
+            }
         }
-        if ctx.key_pressed(Key::ArrowRight) {
-            self.resize(self.rows + 1, self.cols + 1);
+        if ctx.key_pressed(Key::K) {
+            self.tap_tempo();
         }
         if ctx.key_pressed(Key::G) {
             self.grid_mode = match self.grid_mode {
@@ -265,12 +1223,104 @@ impl GameOfLife {
                 GridMode::None => GridMode::Lines,
             };
         }
-        if !self.drawing_mode && ctx.key_pressed(Key::P) {
-            self.paused = !self.paused;
+        if ctx.key_pressed(Key::L) {
+            self.palette = self.palette.next();
         }
-        if self.paused && ctx.key_pressed(Key::S) {
-            // do a single step
-            self.update_cells();
+        if ctx.key_pressed(Key::A) {
+            self.heatmap = !self.heatmap;
+        }
+        if ctx.key_pressed(Key::E) {
+            self.round_cells = !self.round_cells;
+        }
+        if ctx.key_pressed(Key::P) {
+            self.toggle_pause();
+        }
+        if ctx.key_pressed(Key::S) {
+            self.step();
+        }
+        if ctx.mouse_button_pressed(PointerButton::Primary) {
+            let pointer = ctx.mouse_position();
+            if let Some(&(_, button)) = self.buttons.iter().find(|(rect, _)| rect.contains(pointer))
+            {
+                self.press(button);
+            }
+        }
+        if ctx.key_pressed(Key::H) {
+            self.transform(|_, cols, col, row| (cols - 1 - col, row));
+        }
+        if ctx.key_pressed(Key::V) {
+            self.transform(|rows, _, col, row| (col, rows - 1 - row));
+        }
+        if ctx.key_pressed(Key::T) {
+            // rotation needs a square grid
+            if self.rows != self.cols {
+                let size = self.rows.max(self.cols);
+                self.resize(size, size);
+            }
+            self.transform(|rows, _, col, row| (rows - 1 - row, col));
+        }
+        if ctx.key_pressed(Key::Z) {
+            self.recenter();
+        }
+        if ctx.key_pressed(Key::Y) {
+            self.fit_to_pattern();
+        }
+        if ctx.key_pressed(Key::J) {
+            self.invert();
+        }
+        if !self.drawing_mode && ctx.key_pressed(Key::F) {
+            self.skip_generations(self.fast_forward);
+        }
+        if ctx.key_pressed(Key::Q) {
+            self.toggle_colored();
+        }
+        if ctx.key_pressed(Key::W) {
+            self.toggle_worker();
+        }
+        if ctx.key_pressed(Key::U) {
+            self.infinite = !self.infinite;
+        }
+        if ctx.key_pressed(Key::D) {
+            self.show_timing = !self.show_timing;
+        }
+        if ctx.key_pressed(Key::Period) {
+            self.toggle_ticker();
+        }
+        if ctx.key_pressed(Key::F11) {
+            self.full_view = !self.full_view;
+        }
+        if ctx.key_pressed(Key::F4) {
+            self.frame_limiter.next();
+        }
+        if ctx.key_pressed(Key::C) {
+            if self.recording.is_some() {
+                self.stop_recording();
+            } else {
+                self.recording = Some(Recording::new(self.cols, self.rows, &self.cells));
+            }
+        }
+        for (slot, key) in (1..=SLOTS).zip(SLOT_KEYS) {
+            if ctx.key_pressed(key) {
+                if ctx.key_down(SAVE_KEY) {
+                    self.save_snapshot(slot);
+                } else {
+                    self.load_snapshot(slot);
+                }
+            }
+        }
+        if ctx.key_pressed(Key::OpenBracket) {
+            self.seed = self.seed.saturating_sub(1);
+            self.randomize();
+        }
+        if ctx.key_pressed(Key::CloseBracket) {
+            self.seed += 1;
+            self.randomize();
+        }
+        if ctx.key_pressed(Key::PageUp) {
+            self.fast_forward = (self.fast_forward * 10).min(MAX_FAST_FORWARD);
+        }
+        if ctx.key_pressed(Key::PageDown) {
+            self.fast_forward = (self.fast_forward / 10).max(1);
         }
         if self.drawing_mode {
             if ctx.key_pressed(Key::O) {
@@ -278,47 +1328,92 @@ impl GameOfLife {
                     game.load_from_text(&text);
                 });
             }
+            // a second press drops the stamp again
+            if ctx.key_pressed(Key::I) && self.stamp.take().is_none() {
+                ctx.launch_async_update(get_file_as_string(), |game, text| {
+                    game.stamp = Some(stamp_from_text(&text));
+                });
+            }
             if ctx.key_pressed(Key::S) {
                 let text = self.save_to_text();
                 run_future(write_file_from_string(text));
             }
-            if ctx.mouse_button_pressed(PointerButton::Primary) {
-                let mouse_pos = {
-                    let Pos2 { x, y } = ctx.mouse_position();
-                    // convert to grid coordinates
-                    (x - self.grid_rect.min.x, y - self.grid_rect.min.y)
+            if self.colored && ctx.key_pressed(Key::N) {
+                self.brush_species = self.brush_species % SPECIES_COLORS.len() as u8 + 1;
+            }
+            if ctx.key_pressed(Key::B) {
+                self.brush_size = match self.brush_size {
+                    1 => 3,
+                    3 => 5,
+                    _ => 1,
                 };
+            }
+            if let Some((x, y)) = self.hovered_cell {
+                if self.stamp.is_some() {
+                    if ctx.mouse_button_pressed(PointerButton::Primary) {
+                        self.place_stamp(x, y);
+                    }
+                } else if self.brush_size == 1 {
+                    if ctx.mouse_button_pressed(PointerButton::Primary) {
+                        self.toggle_cell(x, y);
+                    }
+                } else if ctx.mouse_button_down(PointerButton::Primary) {
+                    // bigger brushes paint while dragging
+                    self.paint(x, y);
+                }
+            }
+        }
+    }
 
-                let (w, h) = (self.grid_rect.width(), self.grid_rect.height());
-                let cw = w / self.cols as f32;
-                let ch = h / self.rows as f32;
-                let x = (mouse_pos.0 / cw).floor() as usize;
-                let y = (mouse_pos.1 / ch).floor() as usize;
-                let index = self.get_index(x, y);
-                if index < self.cells.len() {
-                    self.cells[index] = !self.cells[index];
+    fn save_snapshot(&mut self, slot: u8) {
+        let snapshot = Snapshot {
+            cols: self.cols,
+            rows: self.rows,
+            cells: self.cells.clone(),
+        };
+        self.snapshots.insert(slot, snapshot);
+        info!("Saved snapshot {slot}");
+    }
+
+    /// replaces the board with a snapshot, it also becomes the loaded board
+    fn load_snapshot(&mut self, slot: u8) {
+        let Some(snapshot) = self.snapshots.get(slot) else {
+            return;
+        };
+        let cells = snapshot.cells.clone();
+        self.resize(snapshot.rows, snapshot.cols);
+        self.cells = cells;
+        if !self.colored {
+            for cell in &mut self.cells {
+                if *cell != DEAD {
+                    *cell = ALIVE;
                 }
             }
         }
+        self.keep_for_reset(ResetTarget::Loaded);
+        self.restart_generations();
     }
 
     fn load_from_text(&mut self, text: &str) {
-        for line in text.lines() {
-            if line.starts_with("//") || line.is_empty() {
-                continue;
-            }
-            let (Ok(x), Ok(y)) = ({
-                let (x, y) = line.split_once(' ').unwrap();
-                let x = x.parse();
-                let y = y.parse();
-                (x, y)
-            }) else {
-                println!("Invalid line: {line}");
-                continue;
-            };
+        for (x, y) in parse_cells(text) {
             let index = self.get_index(x, y);
             if index < self.cells.len() {
-                self.cells[index] = true;
+                self.cells[index] = ALIVE;
+            }
+        }
+        self.keep_for_reset(ResetTarget::Loaded);
+    }
+
+    /// sets the stamp cells with the brush species, its top left corner at
+    /// the given cell, cells past the edges are left out
+    fn place_stamp(&mut self, x: usize, y: usize) {
+        let Some(stamp) = &self.stamp else {
+            return;
+        };
+        for &(dx, dy) in stamp {
+            let (col, row) = (x + dx, y + dy);
+            if col < self.cols && row < self.rows {
+                self.cells[row * self.cols + col] = self.brush_species;
             }
         }
     }
@@ -326,7 +1421,7 @@ impl GameOfLife {
     fn save_to_text(&self) -> String {
         let mut text = String::new();
         for (i, cell) in self.cells.iter().enumerate() {
-            if *cell {
+            if *cell != DEAD {
                 let x = i % self.cols;
                 let y = i / self.cols;
                 text.push_str(&format!("{x} {y}\n"));
@@ -336,6 +1431,94 @@ impl GameOfLife {
     }
 }
 
+/// live cells of the text format, one `x y` line per cell, `//` starts a comment
+fn parse_cells(text: &str) -> Vec<(usize, usize)> {
+    let mut cells = vec![];
+    for line in text.lines() {
+        if line.starts_with("//") || line.is_empty() {
+            continue;
+        }
+        let Some((Ok(x), Ok(y))) = line.split_once(' ').map(|(x, y)| (x.parse(), y.parse())) else {
+            println!("Invalid line: {line}");
+            continue;
+        };
+        cells.push((x, y));
+    }
+    cells
+}
+
+/// cells of a pattern file moved so the pattern starts at (0, 0)
+fn stamp_from_text(text: &str) -> Vec<(usize, usize)> {
+    let cells = parse_cells(text);
+    let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    cells
+        .into_iter()
+        .map(|(x, y)| (x - min_x, y - min_y))
+        .collect()
+}
+
+/// breaks a comma separated help line into lines at most `max_width` wide
+/// in the HUD font, a single entry wider than that gets its own line
+fn wrap(line: &str, max_width: f32) -> String {
+    let mut lines: Vec<String> = vec![];
+    for entry in line.split(", ") {
+        if let Some(last) = lines.last_mut() {
+            let joined = format!("{last}, {entry}");
+            if text_width(&joined) <= max_width {
+                *last = joined;
+                continue;
+            }
+        }
+        lines.push(entry.to_string());
+    }
+    lines.join(",\n")
+}
+
+/// width of `text` laid out in the HUD font
+fn text_width(text: &str) -> f32 {
+    FONTS
+        .layout_no_wrap(
+            text.to_string(),
+            FontId::proportional(HUD_FONT_SIZE),
+            Color32::WHITE,
+        )
+        .size()
+        .x
+}
+
+/// rectangle border drawn inside the given area
+fn outline(ctx: &mut DrawContext<'_>, pos: (f32, f32), size: (f32, f32), color: Color32) {
+    let thickness = 2.0;
+    let (x, y) = (pos.0 + thickness / 2.0, pos.1 + thickness / 2.0);
+    let (w, h) = (size.0 - thickness, size.1 - thickness);
+    ctx.line((x, y), (x + w, y), thickness, color);
+    ctx.line((x + w, y), (x + w, y + h), thickness, color);
+    ctx.line((x + w, y + h), (x, y + h), thickness, color);
+    ctx.line((x, y + h), (x, y), thickness, color);
+}
+
+/// circle inside the given area made of horizontal strips
+fn circle_filled(ctx: &mut DrawContext<'_>, pos: (f32, f32), size: (f32, f32), color: Color32) {
+    const STRIPS: usize = 8;
+    let radius = size.0.min(size.1) / 2.0;
+    let center = (pos.0 + size.0 / 2.0, pos.1 + size.1 / 2.0);
+    let strip_height = radius * 2.0 / STRIPS as f32;
+    for i in 0..STRIPS {
+        // vertical offset of the strip center in -1..1
+        let y = (i as f32 + 0.5) / STRIPS as f32 * 2.0 - 1.0;
+        let half_width = radius * (1.0 - y * y).sqrt();
+        ctx.rect_filled(
+            (
+                center.0 - half_width,
+                center.1 + y * radius - strip_height / 2.0,
+            ),
+            (half_width * 2.0, strip_height),
+            color,
+        );
+    }
+}
+
 fn main() {
     EguiGame::new().run::<GameOfLife>("Convay's Game of Life");
 }
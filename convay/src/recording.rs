@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+
+use egui::Color32;
+
+use crate::{ALIVE, DEAD, Palette, SPECIES_COLORS};
+
+/// most generations kept in one recording
+pub const MAX_FRAMES: usize = 300;
+/// side length of a cell in the exported gif in pixels
+const CELL_PIXELS: usize = 8;
+
+/// palette indices besides the species, which use their cell state
+const BACKGROUND: u8 = 0;
+const SHADE_LIGHT: u8 = SPECIES_COLORS.len() as u8 + 1;
+const SHADE_DARK: u8 = SHADE_LIGHT + 1;
+
+/// how dead cells and gaps look in the exported frames
+#[derive(Clone, Copy)]
+pub enum Style {
+    /// a gap around every cell, like the grid lines
+    Lines,
+    /// checkerboard background
+    Shaded,
+    Plain,
+}
+
+/// Generations captured for a gif export.
+pub struct Recording {
+    cols: usize,
+    rows: usize,
+    frames: Vec<Vec<u8>>,
+}
+
+impl Recording {
+    pub fn new(cols: usize, rows: usize, cells: &[u8]) -> Self {
+        Self {
+            cols,
+            rows,
+            frames: vec![cells.to_vec()],
+        }
+    }
+
+    /// adds a generation, returns false if the recording is full or the grid size changed
+    pub fn push(&mut self, cells: &[u8]) -> bool {
+        if self.frames.len() >= MAX_FRAMES || cells.len() != self.cols * self.rows {
+            return false;
+        }
+        self.frames.push(cells.to_vec());
+        self.frames.len() < MAX_FRAMES
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// encodes all frames as a looping gif in the colors of `palette`,
+    /// `delay` is the time per frame in s,
+    /// without `colored` every species is drawn in the live color
+    pub fn encode(
+        &self,
+        style: Style,
+        palette: Palette,
+        colored: bool,
+        delay: f32,
+    ) -> Result<Vec<u8>, gif::EncodingError> {
+        let width = self.cols * CELL_PIXELS;
+        let height = self.rows * CELL_PIXELS;
+        let size_error = || gif::EncodingError::from(std::io::Error::other("grid too large"));
+        let gif_width = u16::try_from(width).map_err(|_| size_error())?;
+        let gif_height = u16::try_from(height).map_err(|_| size_error())?;
+        // gif delays are in 1/100 s, most viewers ignore values below 2
+        let delay = ((delay * 100.0).round() as u16).max(2);
+
+        let live = palette.live();
+        let mut palette = vec![palette.background()];
+        palette.extend(SPECIES_COLORS);
+        if !colored {
+            palette[usize::from(ALIVE)] = live;
+        }
+        palette.extend([Color32::GRAY, Color32::DARK_GRAY]);
+        // gif palettes have a power of two length
+        palette.resize(palette.len().next_power_of_two(), Color32::BLACK);
+        let palette: Vec<u8> = palette.iter().flat_map(|c| [c.r(), c.g(), c.b()]).collect();
+
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut bytes, gif_width, gif_height, &palette)?;
+            encoder.set_repeat(gif::Repeat::Infinite)?;
+            for cells in &self.frames {
+                let mut pixels = vec![BACKGROUND; width * height];
+                for (i, index) in pixels.iter_mut().enumerate() {
+                    let (x, y) = (i % width, i / width);
+                    let (col, row) = (x / CELL_PIXELS, y / CELL_PIXELS);
+                    let state = match cells[row * self.cols + col] {
+                        DEAD => DEAD,
+                        state if colored => state,
+                        _ => ALIVE,
+                    };
+                    *index = pixel(style, state, col, row, x, y);
+                }
+                let frame = gif::Frame {
+                    width: gif_width,
+                    height: gif_height,
+                    delay,
+                    buffer: Cow::Owned(pixels),
+                    ..Default::default()
+                };
+                encoder.write_frame(&frame)?;
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// palette index of pixel (x, y) in cell (col, row) with the given state
+fn pixel(style: Style, state: u8, col: usize, row: usize, x: usize, y: usize) -> u8 {
+    let alive = state != DEAD;
+    match style {
+        Style::Lines => {
+            let on_line = x % CELL_PIXELS == 0 || y % CELL_PIXELS == 0;
+            if alive && !on_line { state } else { BACKGROUND }
+        }
+        Style::Shaded if !alive => {
+            if row % 2 == col % 2 {
+                SHADE_LIGHT
+            } else {
+                SHADE_DARK
+            }
+        }
+        _ => state,
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use crate::{DEAD, SPECIES_COLORS};
+
+/// storage key prefix, followed by the slot number
+const STORAGE_KEY: &str = "snapshot_";
+/// slots are numbered from 1 to this
+pub const SLOTS: u8 = 9;
+
+/// A saved board together with its size.
+#[derive(Clone)]
+pub struct Snapshot {
+    pub cols: usize,
+    pub rows: usize,
+    pub cells: Vec<u8>,
+}
+
+/// Boards saved in numbered slots, persisted in the app storage.
+#[derive(Default)]
+pub struct Snapshots {
+    slots: BTreeMap<u8, Snapshot>,
+}
+
+impl Snapshots {
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        let Some(storage) = storage else {
+            return Self::default();
+        };
+        let slots = (1..=SLOTS)
+            .filter_map(|slot| {
+                let text = storage.get_string(&format!("{STORAGE_KEY}{slot}"))?;
+                Some((slot, parse_snapshot(&text)?))
+            })
+            .collect();
+        Self { slots }
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        for slot in 1..=SLOTS {
+            // empty slots are stored as empty text
+            let text = self
+                .slots
+                .get(&slot)
+                .map(write_snapshot)
+                .unwrap_or_default();
+            storage.set_string(&format!("{STORAGE_KEY}{slot}"), text);
+        }
+    }
+
+    pub fn get(&self, slot: u8) -> Option<&Snapshot> {
+        self.slots.get(&slot)
+    }
+
+    pub fn insert(&mut self, slot: u8, snapshot: Snapshot) {
+        self.slots.insert(slot, snapshot);
+    }
+
+    /// numbers of the filled slots in order
+    pub fn filled(&self) -> impl Iterator<Item = u8> + '_ {
+        self.slots.keys().copied()
+    }
+}
+
+/// size in the first line, then one `x y state` line per live cell
+fn write_snapshot(snapshot: &Snapshot) -> String {
+    let mut text = format!("{} {}\n", snapshot.cols, snapshot.rows);
+    for (i, &cell) in snapshot.cells.iter().enumerate() {
+        if cell != DEAD {
+            text.push_str(&format!(
+                "{} {} {cell}\n",
+                i % snapshot.cols,
+                i / snapshot.cols
+            ));
+        }
+    }
+    text
+}
+
+fn parse_snapshot(text: &str) -> Option<Snapshot> {
+    let mut lines = text.lines();
+    let (cols, rows) = lines.next()?.split_once(' ')?;
+    let (cols, rows): (usize, usize) = (cols.parse().ok()?, rows.parse().ok()?);
+    if cols == 0 || rows == 0 {
+        return None;
+    }
+    let mut cells = vec![DEAD; cols * rows];
+    for line in lines {
+        let mut parts = line.split(' ').map(str::parse::<usize>);
+        let (Some(Ok(x)), Some(Ok(y)), Some(Ok(state))) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if x < cols && y < rows && state <= SPECIES_COLORS.len() {
+            cells[y * cols + x] = state as u8;
+        }
+    }
+    Some(Snapshot { cols, rows, cells })
+}
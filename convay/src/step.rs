@@ -0,0 +1,228 @@
+use crate::{ALIVE, DEAD, SPECIES_COLORS};
+
+/// read-only view of a board, enough to compute its next generation
+#[derive(Clone, Copy)]
+pub struct Board<'a> {
+    pub cells: &'a [u8],
+    pub cols: usize,
+    pub rows: usize,
+    /// newborn cells inherit the majority species of their parents
+    pub colored: bool,
+}
+
+impl Board<'_> {
+    /// state of a cell after the next step given its live neighbors
+    pub fn apply_rules(&self, col: usize, row: usize, neighbors: u8) -> u8 {
+        // Rules:
+        // A cell keeps its state if it has two neighbors.
+        // A cell becomes active if it has three neighbors.
+        let state = self.cells[row * self.cols + col];
+        match neighbors {
+            2 => state,
+            3 if state != DEAD => state,
+            3 if self.colored => self.inherited_species(col, row),
+            3 => ALIVE,
+            _ => DEAD,
+        }
+    }
+
+    /// live neighbors of a single cell, `Stepper` counts the whole board faster
+    pub fn count_neighbors(&self, col: usize, row: usize) -> u8 {
+        let mut neighbors = 0;
+        for n_row in row.saturating_sub(1)..=(row + 1).min(self.rows - 1) {
+            for n_col in col.saturating_sub(1)..=(col + 1).min(self.cols - 1) {
+                // skip self
+                if n_col == col && n_row == row {
+                    continue;
+                }
+                if self.cells[n_row * self.cols + n_col] != DEAD {
+                    neighbors += 1;
+                }
+            }
+        }
+        neighbors
+    }
+
+    /// species of a cell born from its three live neighbors:
+    /// the majority species, or the missing one if all three differ
+    fn inherited_species(&self, col: usize, row: usize) -> u8 {
+        let mut counts = [0; SPECIES_COLORS.len()];
+        for n_row in row.saturating_sub(1)..=(row + 1).min(self.rows - 1) {
+            for n_col in col.saturating_sub(1)..=(col + 1).min(self.cols - 1) {
+                let state = self.cells[n_row * self.cols + n_col];
+                if state != DEAD {
+                    counts[usize::from(state - 1)] += 1;
+                }
+            }
+        }
+        let species = counts
+            .iter()
+            .position(|&count| count >= 2)
+            .or_else(|| counts.iter().position(|&count| count == 0))
+            .unwrap_or(0);
+        species as u8 + 1
+    }
+}
+
+/// Computes whole generations, the buffers are kept to avoid allocating every step.
+#[derive(Default)]
+pub struct Stepper {
+    row_sums: Vec<u8>,
+    neighbor_counts: Vec<u8>,
+}
+
+impl Stepper {
+    /// writes the generation after `board` into `next`, which has the same size
+    pub fn step(&mut self, board: Board<'_>, next: &mut [u8]) {
+        self.count_all_neighbors(board);
+        for row in 0..board.rows {
+            for col in 0..board.cols {
+                let index = row * board.cols + col;
+                next[index] = board.apply_rules(col, row, self.neighbor_counts[index]);
+            }
+        }
+    }
+
+    /// fills `neighbor_counts` for the whole grid in flat passes:
+    /// sums of three cells along each row, then the sums above and below added up
+    fn count_all_neighbors(&mut self, board: Board<'_>) {
+        let (cols, len) = (board.cols, board.cells.len());
+        self.row_sums.resize(len, 0);
+        self.neighbor_counts.resize(len, 0);
+        for (line, sums) in board
+            .cells
+            .chunks_exact(cols)
+            .zip(self.row_sums.chunks_exact_mut(cols))
+        {
+            let alive = |col: usize| u8::from(line[col] != DEAD);
+            if cols == 1 {
+                sums[0] = alive(0);
+                continue;
+            }
+            sums[0] = alive(0) + alive(1);
+            for (sum, window) in sums[1..cols - 1].iter_mut().zip(line.windows(3)) {
+                *sum = window.iter().map(|&cell| u8::from(cell != DEAD)).sum();
+            }
+            sums[cols - 1] = alive(cols - 2) + alive(cols - 1);
+        }
+        // the own row without the cell itself
+        for ((count, sum), cell) in self
+            .neighbor_counts
+            .iter_mut()
+            .zip(&self.row_sums)
+            .zip(board.cells)
+        {
+            *count = sum - u8::from(*cell != DEAD);
+        }
+        // the row above, the first row has none
+        for (count, sum) in self.neighbor_counts[cols..]
+            .iter_mut()
+            .zip(&self.row_sums[..len - cols])
+        {
+            *count += sum;
+        }
+        // the row below, the last row has none
+        for (count, sum) in self.neighbor_counts[..len - cols]
+            .iter_mut()
+            .zip(&self.row_sums[cols..])
+        {
+            *count += sum;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// board of random states, species included when `colored`
+    fn random_cells(rng: &mut fastrand::Rng, len: usize, colored: bool) -> Vec<u8> {
+        let max = if colored {
+            SPECIES_COLORS.len() as u8
+        } else {
+            ALIVE
+        };
+        (0..len)
+            .map(|_| {
+                if rng.u32(0..100) < 40 {
+                    rng.u8(ALIVE..=max)
+                } else {
+                    DEAD
+                }
+            })
+            .collect()
+    }
+
+    /// the next generation counted cell by cell
+    fn naive_step(board: Board<'_>) -> Vec<u8> {
+        let mut next = vec![DEAD; board.cells.len()];
+        for row in 0..board.rows {
+            for col in 0..board.cols {
+                let neighbors = board.count_neighbors(col, row);
+                next[row * board.cols + col] = board.apply_rules(col, row, neighbors);
+            }
+        }
+        next
+    }
+
+    fn assert_matches_naive(cols: usize, rows: usize) {
+        let mut rng = fastrand::Rng::with_seed((cols * 1000 + rows) as u64);
+        let mut stepper = Stepper::default();
+        for colored in [false, true] {
+            for _ in 0..20 {
+                let cells = random_cells(&mut rng, cols * rows, colored);
+                let board = Board {
+                    cells: &cells,
+                    cols,
+                    rows,
+                    colored,
+                };
+                let mut next = vec![DEAD; cells.len()];
+                stepper.step(board, &mut next);
+                assert_eq!(next, naive_step(board), "{cols}x{rows}, colored: {colored}");
+            }
+        }
+    }
+
+    #[test]
+    fn step_matches_naive_count() {
+        assert_matches_naive(17, 13);
+        assert_matches_naive(40, 40);
+    }
+
+    #[test]
+    fn step_matches_naive_count_on_a_single_row_or_column() {
+        for len in [1, 2, 3, 10] {
+            assert_matches_naive(len, 1);
+            assert_matches_naive(1, len);
+        }
+    }
+
+    #[test]
+    fn step_matches_naive_count_on_narrow_boards() {
+        // every cell touches an edge
+        for len in [2, 3, 9] {
+            assert_matches_naive(len, 2);
+            assert_matches_naive(2, len);
+        }
+    }
+
+    #[test]
+    fn stepper_reuses_its_buffers_across_sizes() {
+        // a smaller board after a larger one must not read stale counts
+        let mut rng = fastrand::Rng::with_seed(3);
+        let mut stepper = Stepper::default();
+        for (cols, rows) in [(30, 30), (5, 7), (1, 4), (12, 1)] {
+            let cells = random_cells(&mut rng, cols * rows, false);
+            let board = Board {
+                cells: &cells,
+                cols,
+                rows,
+                colored: false,
+            };
+            let mut next = vec![DEAD; cells.len()];
+            stepper.step(board, &mut next);
+            assert_eq!(next, naive_step(board), "{cols}x{rows}");
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::mpsc::{Receiver, Sender, TryRecvError, channel};
+
+use web_time::{Duration, Instant};
+
+use crate::step::{Board, Stepper};
+
+/// A board handed to the worker, owned so the game can keep changing its own.
+pub struct Job {
+    pub cells: Vec<u8>,
+    pub cols: usize,
+    pub rows: usize,
+    pub colored: bool,
+}
+
+/// The next generation together with the board it was computed from.
+pub struct Done {
+    pub job: Job,
+    pub next: Vec<u8>,
+    /// time the worker spent on the step
+    pub duration: Duration,
+}
+
+/// Computes steps on a background thread, one job at a time.
+///
+/// The worker only ever sees copies of the board, so resizing or editing
+/// the grid while a job runs is safe; the game drops results whose input
+/// no longer matches its cells.
+pub struct Worker {
+    jobs: Sender<Job>,
+    results: Receiver<Done>,
+    busy: bool,
+}
+
+impl Worker {
+    /// starts the thread, fails on targets without threads like the web
+    pub fn spawn() -> std::io::Result<Self> {
+        let (jobs, job_receiver) = channel::<Job>();
+        let (result_sender, results) = channel();
+        std::thread::Builder::new()
+            .name("convay-step".to_string())
+            .spawn(move || {
+                let mut stepper = Stepper::default();
+                // ends once the worker and with it the job sender is dropped
+                for job in job_receiver {
+                    let start = Instant::now();
+                    let mut next = vec![0; job.cells.len()];
+                    let board = Board {
+                        cells: &job.cells,
+                        cols: job.cols,
+                        rows: job.rows,
+                        colored: job.colored,
+                    };
+                    stepper.step(board, &mut next);
+                    let duration = start.elapsed();
+                    if result_sender
+                        .send(Done {
+                            job,
+                            next,
+                            duration,
+                        })
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self {
+            jobs,
+            results,
+            busy: false,
+        })
+    }
+
+    pub fn busy(&self) -> bool {
+        self.busy
+    }
+
+    pub fn submit(&mut self, job: Job) {
+        self.busy = self.jobs.send(job).is_ok();
+    }
+
+    /// the finished job, if there is one
+    pub fn poll(&mut self) -> Option<Done> {
+        match self.results.try_recv() {
+            Ok(done) => {
+                self.busy = false;
+                Some(done)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.busy = false;
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+/// storage key of the leaderboard entries
+const STORAGE_KEY: &str = "leaderboard";
+/// storage key of the plain highscore used before initials existed
+const LEGACY_KEY: &str = "highscore";
+const MAX_ENTRIES: usize = 5;
+const INITIALS_LEN: usize = 3;
+
+pub struct Entry {
+    pub initials: String,
+    pub score: u32,
+}
+
+/// Best scores with player initials, highest first.
+#[derive(Default)]
+pub struct Leaderboard {
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        let Some(storage) = storage else {
+            return Self::default();
+        };
+        let mut entries: Vec<Entry> = storage
+            .get_string(STORAGE_KEY)
+            .map(|text| text.lines().filter_map(parse_entry).collect())
+            .unwrap_or_default();
+        if entries.is_empty() {
+            // keep a highscore saved by an older version
+            let legacy = storage
+                .get_string(LEGACY_KEY)
+                .and_then(|s| s.parse::<u32>().ok())
+                .unwrap_or(0);
+            if legacy > 0 {
+                entries.push(Entry {
+                    initials: "???".to_string(),
+                    score: legacy,
+                });
+            }
+        }
+        entries.sort_by(|a, b| b.score.cmp(&a.score));
+        entries.truncate(MAX_ENTRIES);
+        Self { entries }
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        let text = self
+            .entries
+            .iter()
+            .map(|e| format!("{} {}", e.initials, e.score))
+            .collect::<Vec<_>>()
+            .join("\n");
+        storage.set_string(STORAGE_KEY, text);
+        storage.set_string(LEGACY_KEY, self.best_score().to_string());
+    }
+
+    pub fn top(&self) -> Option<&Entry> {
+        self.entries.first()
+    }
+
+    pub fn best_score(&self) -> u32 {
+        self.top().map_or(0, |e| e.score)
+    }
+
+    /// true if the score would make it onto the board
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.last().is_some_and(|e| score > e.score))
+    }
+
+    pub fn insert(&mut self, initials: String, score: u32) {
+        // insert after entries with the same score so older entries stay on top
+        let index = self.entries.partition_point(|e| e.score >= score);
+        self.entries.insert(index, Entry { initials, score });
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    let (initials, score) = line.split_once(' ')?;
+    Some(Entry {
+        initials: initials.to_string(),
+        score: score.parse().ok()?,
+    })
+}
+
+/// On-screen entry of the initials for a new leaderboard score.
+pub struct InitialsEntry {
+    letters: [u8; INITIALS_LEN],
+    cursor: usize,
+}
+
+impl Default for InitialsEntry {
+    fn default() -> Self {
+        Self {
+            letters: [b'A'; INITIALS_LEN],
+            cursor: 0,
+        }
+    }
+}
+
+impl InitialsEntry {
+    pub fn next_letter(&mut self) {
+        let letter = &mut self.letters[self.cursor];
+        *letter = if *letter == b'Z' { b'A' } else { *letter + 1 };
+    }
+
+    pub fn prev_letter(&mut self) {
+        let letter = &mut self.letters[self.cursor];
+        *letter = if *letter == b'A' { b'Z' } else { *letter - 1 };
+    }
+
+    pub fn move_cursor(&mut self, right: bool) {
+        self.cursor = if right {
+            (self.cursor + 1).min(INITIALS_LEN - 1)
+        } else {
+            self.cursor.saturating_sub(1)
+        };
+    }
+
+    pub fn text(&self) -> String {
+        self.letters.iter().map(|&l| char::from(l)).collect()
+    }
+
+    /// letters with the selected one in brackets
+    pub fn display(&self) -> String {
+        self.letters
+            .iter()
+            .enumerate()
+            .map(|(i, &l)| {
+                if i == self.cursor {
+                    format!("[{}]", char::from(l))
+                } else {
+                    format!(" {} ", char::from(l))
+                }
+            })
+            .collect()
+    }
+}
@@ -5,71 +5,649 @@
     clippy::cast_possible_truncation
 )]
 
+mod leaderboard;
+
+use std::cmp::Ordering;
 use std::collections::VecDeque;
 
-use egui::{Color32, Key, Pos2, Vec2};
-use egui_game::utils::random_u32;
+use common::{Achievements, Canvas, Screenshot, Settings, arc, draw_game_over, triangle_filled};
+use egui::{Color32, Key, PointerButton, Pos2, Rect, Vec2};
 use egui_game::{Anchor, EguiGame};
 use egui_game::{DrawContext, Game, UpdateContext};
+use leaderboard::{InitialsEntry, Leaderboard};
 
 /// time per tick in s
 const START_TICK: f32 = 0.5;
+/// the tick is multiplied by this for every apple, down to `MIN_TICK`
+const SPEED_UP: f32 = 0.9;
+/// the tick is shortened by this for every apple with linear acceleration
+const LINEAR_SPEED_UP: f32 = 0.02;
+const MIN_TICK: f32 = 0.08;
+/// slower start per step of adaptive difficulty
+const EASE_STEP: f32 = 0.04;
+const MAX_EASE: u32 = 5;
+/// time before the snake starts moving in s
+const COUNTDOWN: f32 = 3.0;
+/// swipe distance in points that turns the snake
+const SWIPE_DISTANCE: f32 = 30.;
+/// time a vacated cell keeps fading in s
+const TRAIL_TIME: f32 = 0.4;
+/// time in s a dead snake takes to dissolve before the game over screen
+const DEATH_TIME: f32 = 1.0;
+/// gap between the body and the cell border as a fraction of the cell
+const BODY_INSET: f32 = 0.1;
+/// side length of a cell in screenshots in pixels
+const SCREENSHOT_CELL: f32 = 16.;
+/// corner radius of body segments and the head as a fraction of the cell
+const BODY_RADIUS: f32 = 0.25;
+const HEAD_RADIUS: f32 = 0.45;
+const MAX_APPLES: usize = 5;
+/// ticks between two border steps in sudden death
+const SHRINK_INTERVAL: usize = 50;
+/// the safe zone never gets narrower than this many cells
+const MIN_SAFE_SIZE: f32 = 6.;
+/// cells in front of a head that never get a new wall
+const WALL_DISTANCE: usize = 3;
+/// one in this many ticks spawns bonus food while there is none
+const BONUS_CHANCE: usize = 40;
+/// ticks before uneaten bonus food disappears
+const BONUS_TICKS: usize = 25;
+const BONUS_SCORE: u32 = 3;
+/// ticks after an apple in which the next one extends the streak,
+/// counted in ticks instead of seconds so replays score the same
+const STREAK_TICKS: usize = 12;
+/// ticks between two steps of moving apples
+const MOUSE_INTERVAL: usize = 3;
+/// extra points for catching a moving apple
+const MOUSE_BONUS: u32 = 2;
+/// free cells compared when spawning apples away from the head
+const SPAWN_SAMPLES: usize = 5;
+/// continues per run when continues are on
+const MAX_CONTINUES: usize = 1;
+const CONTINUE_PENALTY: u32 = 5;
+/// length of the snake after a continue
+const CONTINUE_LENGTH: usize = 3;
+/// cells around a respawn that should be free of walls, snakes and deadly edges
+const CONTINUE_CLEARANCE: f32 = 2.;
+/// random cells tried for a respawn before taking any free one
+const CONTINUE_SAMPLES: usize = 50;
 
-#[derive(Default)]
-struct Snake {
+/// side length of an on-screen direction button in points
+const BUTTON_SIZE: f32 = 60.;
+
+/// steering keys in the order left, right, up, down
+const PLAYER_1_KEYS: [Key; 4] = [
+    Key::ArrowLeft,
+    Key::ArrowRight,
+    Key::ArrowUp,
+    Key::ArrowDown,
+];
+const PLAYER_2_KEYS: [Key; 4] = [Key::A, Key::D, Key::W, Key::S];
+/// turns that can wait for the next ticks, one is applied per tick
+const MAX_QUEUED_TURNS: usize = 2;
+/// keys toggling wrapping of the left, right, top and bottom edge
+const EDGE_KEYS: [Key; 4] = [Key::Num1, Key::Num2, Key::Num3, Key::Num4];
+
+struct Player {
     segments: VecDeque<Pos2>,
-    apple: Pos2,
     direction: Vec2,
-    tick: f32,
+    /// direction of the last move, turning back into it is blocked
+    last_move: Vec2,
+    /// turns pressed faster than the snake moves, oldest first
+    turns: VecDeque<Vec2>,
     score: u32,
+    /// apples eaten in quick succession, each one is worth the streak length
+    streak: u32,
+    /// ticks left to extend the streak
+    streak_ticks: usize,
+    color: Color32,
+    dead: bool,
+}
+
+impl Player {
+    fn new(start: Pos2, direction: Vec2, color: Color32) -> Self {
+        Self {
+            segments: [start].into(),
+            direction,
+            last_move: direction,
+            turns: VecDeque::new(),
+            score: 0,
+            streak: 0,
+            streak_ticks: 0,
+            color,
+            dead: false,
+        }
+    }
+
+    fn steer(&mut self, ctx: &mut UpdateContext<'_, Snake>, keys: [Key; 4]) {
+        let [left, right, up, down] = keys;
+        if ctx.key_pressed(left) {
+            self.turn(Vec2::new(-1.0, 0.0));
+        } else if ctx.key_pressed(right) {
+            self.turn(Vec2::new(1.0, 0.0));
+        } else if ctx.key_pressed(up) {
+            self.turn(Vec2::new(0.0, -1.0));
+        } else if ctx.key_pressed(down) {
+            self.turn(Vec2::new(0.0, 1.0));
+        }
+    }
+
+    /// scores an apple, apples in quick succession earn a growing bonus
+    fn eat(&mut self) {
+        self.streak = if self.streak_ticks > 0 {
+            self.streak + 1
+        } else {
+            1
+        };
+        self.streak_ticks = STREAK_TICKS;
+        self.score += self.streak;
+    }
+
+    /// ends the streak once its window runs out
+    fn tick_streak(&mut self) {
+        self.streak_ticks = self.streak_ticks.saturating_sub(1);
+        if self.streak_ticks == 0 {
+            self.streak = 0;
+        }
+    }
+
+    /// queues a turn, presses that wouldn't change the queued direction are ignored
+    fn turn(&mut self, direction: Vec2) {
+        let queued = self.turns.back().copied().unwrap_or(self.direction);
+        let reverse = self.segments.len() > 1 && direction == -queued;
+        if self.turns.len() >= MAX_QUEUED_TURNS || direction == queued || reverse {
+            return;
+        }
+        self.turns.push_back(direction);
+    }
+
+    /// applies the oldest queued turn before a tick
+    fn next_turn(&mut self) {
+        let Some(direction) = self.turns.pop_front() else {
+            return;
+        };
+        // a longer snake would run into itself
+        if self.segments.len() > 1 && direction == -self.last_move {
+            return;
+        }
+        self.direction = direction;
+    }
+}
+
+/// how new apples are placed
+#[derive(Clone, Copy, Default)]
+enum Spawn {
+    /// any free cell, all equally likely
+    #[default]
+    Scan,
+    /// farthest from the heads out of a few random free cells
+    OpenSpace,
+}
+
+/// board dimensions, the field on screen keeps their aspect ratio
+#[derive(Clone, Copy, Default)]
+enum Board {
+    #[default]
+    Wide,
+    Square,
+    Tall,
+}
+
+impl Board {
+    fn size(self) -> Vec2 {
+        match self {
+            Board::Wide => Vec2::new(30., 20.),
+            Board::Square => Vec2::new(20., 20.),
+            Board::Tall => Vec2::new(15., 25.),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Board::Wide => Board::Square,
+            Board::Square => Board::Tall,
+            Board::Tall => Board::Wide,
+        }
+    }
+}
+
+/// how quickly walls appear while playing
+#[derive(Clone, Copy, Default)]
+enum Walls {
+    #[default]
+    Off,
+    Slow,
+    Fast,
+}
+
+impl Walls {
+    /// ticks between two new walls
+    fn interval(self) -> usize {
+        match self {
+            Walls::Off => usize::MAX,
+            Walls::Slow => 40,
+            Walls::Fast => 20,
+        }
+    }
+
+    /// most walls on the board
+    fn cap(self) -> usize {
+        match self {
+            Walls::Off => 0,
+            Walls::Slow => 10,
+            Walls::Fast => 25,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Walls::Off => Walls::Slow,
+            Walls::Slow => Walls::Fast,
+            Walls::Fast => Walls::Off,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Walls::Off => "off",
+            Walls::Slow => "slow",
+            Walls::Fast => "fast",
+        }
+    }
+}
+
+/// how the tick shrinks with every apple
+#[derive(Clone, Copy, Default)]
+enum Acceleration {
+    /// constant speed
+    None,
+    Linear,
+    #[default]
+    Geometric,
+}
+
+impl Acceleration {
+    /// tick after eating an apple, never below `MIN_TICK`
+    fn next_tick(self, tick: f32) -> f32 {
+        let tick = match self {
+            Acceleration::None => tick,
+            Acceleration::Linear => tick - LINEAR_SPEED_UP,
+            Acceleration::Geometric => tick * SPEED_UP,
+        };
+        tick.max(MIN_TICK)
+    }
+
+    /// number of speed ups that lead from `start` to `tick`, starting at level 1
+    fn level(self, start: f32, tick: f32) -> u32 {
+        let speed_ups = match self {
+            Acceleration::None => 0.0,
+            Acceleration::Linear => (start - tick) / LINEAR_SPEED_UP,
+            Acceleration::Geometric => (tick / start).ln() / SPEED_UP.ln(),
+        };
+        speed_ups.round() as u32 + 1
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Acceleration::None => Acceleration::Linear,
+            Acceleration::Linear => Acceleration::Geometric,
+            Acceleration::Geometric => Acceleration::None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Acceleration::None => "constant",
+            Acceleration::Linear => "linear",
+            Acceleration::Geometric => "geometric",
+        }
+    }
+}
+
+/// fixed wall layout the game starts with
+#[derive(Clone, Copy, Default)]
+enum Level {
+    #[default]
+    Open,
+    Cross,
+    Ring,
+    Corridors,
+}
+
+impl Level {
+    /// wall cells for a board of the given size, the start cells in the
+    /// corners and the rows along the top and bottom stay free
+    fn walls(self, grid_size: Vec2) -> Vec<Pos2> {
+        let (w, h) = (grid_size.x as u32, grid_size.y as u32);
+        let cell = |x: u32, y: u32| Pos2::new(x as f32, y as f32);
+        match self {
+            Level::Open => vec![],
+            Level::Cross => (w / 4..w * 3 / 4)
+                .map(|x| cell(x, h / 2))
+                .chain((h / 4..h * 3 / 4).map(|y| cell(w / 2, y)))
+                .collect(),
+            Level::Ring => {
+                let (left, right) = (w / 4, w * 3 / 4);
+                let (top, bottom) = (h / 4, h * 3 / 4);
+                // each side has a gap in the middle to get inside
+                (left..=right)
+                    .filter(|&x| x != w / 2)
+                    .flat_map(|x| [cell(x, top), cell(x, bottom)])
+                    .chain(
+                        (top + 1..bottom)
+                            .filter(|&y| y != h / 2)
+                            .flat_map(|y| [cell(left, y), cell(right, y)]),
+                    )
+                    .collect()
+            }
+            Level::Corridors => [w / 4, w / 2, w * 3 / 4]
+                .into_iter()
+                .flat_map(|x| (2..h - 2).map(move |y| cell(x, y)))
+                .collect(),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Level::Open => Level::Cross,
+            Level::Cross => Level::Ring,
+            Level::Ring => Level::Corridors,
+            Level::Corridors => Level::Open,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Open => "open",
+            Level::Cross => "cross",
+            Level::Ring => "ring",
+            Level::Corridors => "corridors",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Level::Open, Level::Cross, Level::Ring, Level::Corridors]
+            .into_iter()
+            .find(|l| l.name() == name)
+    }
+}
+
+/// settings that survive a restart
+#[derive(Clone, Copy)]
+struct Options {
+    two_player: bool,
+    hide_trail: bool,
+    spawn: Spawn,
+    /// practice mode, walls stop the snake and it passes through itself
+    invincible: bool,
+    /// on-screen direction buttons for touch screens
+    dpad: bool,
+    /// highlights the next cell and points to the nearest apple
+    assist: bool,
+    board: Board,
+    /// apples on the board at the same time
+    apples: usize,
+    walls: Walls,
+    /// edges the snake passes through to the opposite side,
+    /// in the order left, right, top, bottom
+    wrap: [bool; 4],
+    /// sudden death, the border closes in over time
+    shrink: bool,
+    level: Level,
+    acceleration: Acceleration,
+    /// apples run away from the snakes
+    moving_apples: bool,
+    /// single-player runs start slower after runs that didn't beat the session best
+    adaptive: bool,
+    /// a crash can be continued at a safe spot for a score penalty
+    continues: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            two_player: false,
+            hide_trail: false,
+            spawn: Spawn::default(),
+            invincible: false,
+            dpad: false,
+            assist: false,
+            board: Board::default(),
+            apples: 1,
+            walls: Walls::default(),
+            wrap: [false; 4],
+            shrink: false,
+            level: Level::default(),
+            acceleration: Acceleration::default(),
+            moving_apples: false,
+            adaptive: false,
+            continues: false,
+        }
+    }
+}
+
+/// recent performance for the adaptive difficulty, kept for the session only
+#[derive(Clone, Copy, Default)]
+struct Adaptive {
+    /// steps the next run starts slower, up to `MAX_EASE`
+    ease: u32,
+    /// best single-player score of the session
+    best: u32,
+}
+
+impl Adaptive {
+    /// eases off after a run below the session best, ramps back after a better one
+    fn record(&mut self, score: u32) {
+        if score > self.best {
+            self.best = score;
+            self.ease = self.ease.saturating_sub(1);
+        } else {
+            self.ease = (self.ease + 1).min(MAX_EASE);
+        }
+    }
+
+    fn start_tick(self) -> f32 {
+        START_TICK + self.ease as f32 * EASE_STEP
+    }
+}
+
+/// food worth extra points that despawns when its time runs out
+struct Bonus {
+    pos: Pos2,
+    ticks_left: usize,
+}
+
+#[derive(Default)]
+struct Snake {
+    /// one snake in the default mode, two in two-player mode
+    players: Vec<Player>,
+    apples: Vec<Pos2>,
+    bonus: Option<Bonus>,
+    /// ticks since the apples last moved
+    mouse_ticks: usize,
+    /// walls of the level followed by those spawned during the game,
+    /// deadly like the border
+    walls: Vec<Pos2>,
+    /// number of walls at the start of `walls` that belong to the level
+    level_walls: usize,
+    /// ticks since the last wall appeared
+    wall_ticks: usize,
+    /// rows and columns at each side that are lethal in sudden death
+    inset: f32,
+    /// ticks since the border last closed in
+    shrink_ticks: usize,
+    tick: f32,
+    /// tick the run started with, slower than `START_TICK` if it was eased
+    start_tick: f32,
+    adaptive: Adaptive,
     grid_size: Vec2,
     elapsed: f32,
+    /// remaining countdown before the first tick
+    countdown: f32,
     collision: bool,
-    highscore: u32,
+    /// remaining time of the death animation
+    dying: f32,
+    continues_left: usize,
+    /// ticks at which the run was continued, so replays continue there too
+    continued_at: Vec<usize>,
+    leaderboard: Leaderboard,
+    /// set while the player enters initials for a new leaderboard score
+    initials: Option<InitialsEntry>,
+    options: Options,
+    /// pointer position the current swipe is measured from
+    swipe_start: Option<Pos2>,
+    /// seed of the apple placement, together with `moves` it defines the game
+    seed: u64,
+    rng: fastrand::Rng,
+    /// directions of all players at every tick
+    moves: Vec<Vec<Vec2>>,
+    /// set while playing back recorded moves, counts the replayed ticks
+    replay: Option<usize>,
+    /// recently vacated cells with their color and remaining fade time
+    trail: Vec<(Pos2, Color32, f32)>,
+    settings: Settings,
+    achievements: Achievements,
+    screenshot: Screenshot,
 }
 
 impl Game for Snake {
     fn new(storage: Option<&dyn eframe::Storage>) -> Self {
-        let highscore = storage
-            .and_then(|s| s.get_string("highscore"))
-            .and_then(|s| s.parse::<u32>().ok())
-            .unwrap_or(0);
-        let grid_size = Vec2::new(30., 20.);
-        Self {
-            segments: [Pos2::new(0.0, 0.0)].into(),
-            apple: random_pos(
-                grid_size.x as u32,
-                grid_size.y as u32,
-                &[Pos2::new(0.0, 0.0)],
-            ),
-            direction: Vec2::new(1.0, 0.0),
-            tick: START_TICK,
-            grid_size,
-            highscore,
+        let level = storage
+            .and_then(|s| s.get_string("level"))
+            .and_then(|s| Level::from_name(&s))
+            .unwrap_or_default();
+        let options = Options {
+            level,
             ..Default::default()
-        }
+        };
+        let mut snake = Self::start(Leaderboard::load(storage), options);
+        snake.settings = Settings::load(storage).with_shapes();
+        snake.achievements = Achievements::load(storage);
+        snake
     }
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        storage.set_string("highscore", self.highscore.to_string());
+        self.leaderboard.save(storage);
+        storage.set_string("level", self.options.level.name().to_string());
+        self.settings.save(storage);
+        self.achievements.save(storage);
     }
 
-    fn update(&mut self, ctx: &mut UpdateContext<Self>, delta: f32, _size: Vec2) {
+    fn update(&mut self, ctx: &mut UpdateContext<Self>, delta: f32, size: Vec2) {
+        self.screenshot.update(delta);
+        if self.screenshot.requested(ctx) {
+            match self.screenshot_canvas().encode() {
+                Ok(gif) => self.screenshot.save("snake", gif),
+                Err(err) => log::warn!("failed to encode the screenshot: {err}"),
+            }
+        }
+        if self.settings.update(ctx) {
+            return;
+        }
+        self.achievements.update(delta);
+        let delta = delta * self.settings.time_scale;
+        if ctx.key_pressed(Key::F) {
+            self.options.hide_trail = !self.options.hide_trail;
+        }
+        if ctx.key_pressed(Key::T) {
+            self.options.dpad = !self.options.dpad;
+        }
+        if ctx.key_pressed(Key::J) {
+            self.options.assist = !self.options.assist;
+        }
+        for (_, _, time) in &mut self.trail {
+            *time -= delta;
+        }
+        self.trail.retain(|(_, _, time)| *time > 0.0);
+        if ctx.key_pressed(Key::I) && self.initials.is_none() {
+            self.options.invincible = !self.options.invincible;
+            self.reset();
+            return;
+        }
+        if self.collision && self.dying > 0.0 {
+            self.dying -= delta;
+            return;
+        }
         if self.collision {
-            if ctx.key_pressed(Key::R) {
+            let replayed_continue = self
+                .replay
+                .is_some_and(|tick| self.continued_at.contains(&tick));
+            if self.initials.is_some() {
+                self.handle_initials_input(ctx);
+            } else if replayed_continue {
+                self.continue_run();
+            } else if self.can_continue() {
+                if ctx.key_pressed(Key::Space) {
+                    self.continue_run();
+                } else if ctx.key_pressed(Key::Enter) {
+                    self.continues_left = 0;
+                    self.end_run();
+                }
+            } else if ctx.key_pressed(Key::R) {
+                self.reset();
+            } else if ctx.key_pressed(Key::P) {
+                self.start_replay();
+            } else if ctx.key_pressed(Key::M) {
+                self.options.two_player = !self.options.two_player;
+                self.reset();
+            } else if ctx.key_pressed(Key::H) {
+                self.options.shrink = !self.options.shrink;
+                self.reset();
+            } else if ctx.key_pressed(Key::B) {
+                self.options.walls = self.options.walls.next();
+                self.reset();
+            } else if let Some(edge) = EDGE_KEYS.iter().position(|&key| ctx.key_pressed(key)) {
+                self.options.wrap[edge] = !self.options.wrap[edge];
+                self.reset();
+            } else if ctx.key_pressed(Key::N) {
+                self.options.apples = self.options.apples % MAX_APPLES + 1;
+                self.reset();
+            } else if ctx.key_pressed(Key::G) {
+                self.options.board = self.options.board.next();
+                self.reset();
+            } else if ctx.key_pressed(Key::L) {
+                self.options.level = self.options.level.next();
+                self.reset();
+            } else if ctx.key_pressed(Key::C) {
+                self.options.moving_apples = !self.options.moving_apples;
+                self.reset();
+            } else if ctx.key_pressed(Key::V) {
+                self.options.acceleration = self.options.acceleration.next();
+                self.reset();
+            } else if ctx.key_pressed(Key::E) {
+                self.options.adaptive = !self.options.adaptive;
+                self.reset();
+            } else if ctx.key_pressed(Key::K) {
+                self.options.continues = !self.options.continues;
+                self.reset();
+            } else if ctx.key_pressed(Key::O) {
+                self.options.spawn = match self.options.spawn {
+                    Spawn::Scan => Spawn::OpenSpace,
+                    Spawn::OpenSpace => Spawn::Scan,
+                };
                 self.reset();
             }
             return;
         }
-        // handle input
-        if ctx.key_pressed(Key::ArrowLeft) {
-            self.direction = Vec2::new(-1.0, 0.0);
-        } else if ctx.key_pressed(Key::ArrowRight) {
-            self.direction = Vec2::new(1.0, 0.0);
-        } else if ctx.key_pressed(Key::ArrowUp) {
-            self.direction = Vec2::new(0.0, -1.0);
-        } else if ctx.key_pressed(Key::ArrowDown) {
-            self.direction = Vec2::new(0.0, 1.0);
+        // replays and practice runs don't end on their own
+        if (self.replay.is_some() || self.options.invincible) && ctx.key_pressed(Key::R) {
+            self.reset();
+            return;
+        }
+        if self.replay.is_none() {
+            // handle input
+            let keys = [PLAYER_1_KEYS, PLAYER_2_KEYS];
+            for (player, keys) in self.players.iter_mut().zip(keys) {
+                player.steer(ctx, keys);
+            }
+            self.handle_swipe(ctx);
+            if self.options.dpad {
+                self.handle_dpad(ctx, size);
+            }
+        }
+        if self.countdown > 0.0 {
+            self.countdown -= delta;
+            return;
         }
 
         // update snake position
@@ -78,115 +656,1149 @@ impl Game for Snake {
             return;
         }
         self.elapsed = 0.0;
-        // move snake
-        let new_head = self.segments[0] + self.direction;
-        // check for collision with apple
-        if new_head == self.apple {
-            self.segments.push_front(self.apple);
-            self.apple = random_pos(
-                self.grid_size.x as u32,
-                self.grid_size.y as u32,
-                self.segments.make_contiguous(),
-            );
-            self.score += 1;
-            self.tick *= 0.9;
-        } else {
-            // check for collision with walls or snake
-            if new_head.x < 0.0
-                || new_head.x >= self.grid_size.x
-                || new_head.y < 0.0
-                || new_head.y >= self.grid_size.y
-                || self.segments.contains(&new_head)
-            {
-                // game over
-                self.collision = true;
-                self.highscore = self.score.max(self.highscore);
+        if let Some(tick) = &mut self.replay {
+            let Some(directions) = self.moves.get(*tick) else {
                 return;
+            };
+            for (player, &direction) in self.players.iter_mut().zip(directions) {
+                player.direction = direction;
+            }
+            *tick += 1;
+        } else {
+            for player in &mut self.players {
+                player.next_turn();
+            }
+            self.moves
+                .push(self.players.iter().map(|p| p.direction).collect());
+        }
+        self.step();
+        if !self.collision {
+            self.spawn_walls();
+            self.update_bonus();
+            if self.options.moving_apples {
+                self.move_apples();
+            }
+            if self.options.shrink {
+                self.shrink_border();
             }
-            // move snake
-            self.segments.push_front(new_head);
-            self.segments.pop_back();
         }
     }
 
     fn draw(&mut self, ctx: &mut DrawContext<'_>, size: Vec2) {
+        // the aspect follows the board so cells stay square
         ctx.sub_rect_margin(
             self.grid_size.x / self.grid_size.y,
             40.,
-            Some(Color32::GRAY),
+            Some(self.settings.theme.background()),
             |ctx, size| {
-                let w = size.x / self.grid_size.x;
-                let h = size.y / self.grid_size.y;
+                let Vec2 { x: w, y: h } = cell_size(size, self.grid_size);
 
-                let head_color = if self.collision {
-                    Color32::RED
+                if !self.options.hide_trail {
+                    for (cell, color, time) in &self.trail {
+                        let alpha = 0.5 * time / TRAIL_TIME;
+                        ctx.rect_filled(
+                            (cell.x * w, cell.y * h),
+                            (w, h),
+                            color.gamma_multiply(alpha),
+                        );
+                    }
+                }
+                if self.options.assist && !self.collision {
+                    self.draw_assist(ctx, Vec2::new(w, h));
+                }
+                // dead snakes dissolve from the tail, the head stays
+                let remaining = (self.dying / DEATH_TIME).clamp(0.0, 1.0);
+                for player in &self.players {
+                    let length = if player.dead {
+                        ((player.segments.len() as f32 * remaining).ceil() as usize).max(1)
+                    } else {
+                        player.segments.len()
+                    };
+                    draw_player(ctx, player, length, Vec2::new(w, h));
+                }
+                if self.inset > 0.0 {
+                    // lethal border around the safe zone
+                    let border = Color32::from_rgba_unmultiplied(200, 0, 0, 80);
+                    let (iw, ih) = (self.inset * w, self.inset * h);
+                    ctx.rect_filled((0.0, 0.0), (size.x, ih), border);
+                    ctx.rect_filled((0.0, size.y - ih), (size.x, ih), border);
+                    ctx.rect_filled((0.0, ih), (iw, size.y - ih * 2.), border);
+                    ctx.rect_filled((size.x - iw, ih), (iw, size.y - ih * 2.), border);
+                }
+                for wall in &self.walls {
+                    ctx.rect_filled(
+                        (wall.x * w, wall.y * h),
+                        (w, h),
+                        Color32::from_rgb(140, 70, 20),
+                    );
+                }
+                let apple_color = if self.options.moving_apples {
+                    Color32::LIGHT_GRAY
                 } else {
-                    Color32::WHITE
+                    Color32::GREEN
                 };
-                ctx.rect_filled(
-                    (self.segments[0].x * w, self.segments[0].y * h),
-                    (w, h),
-                    head_color,
-                );
-                for segment in self.segments.iter().skip(1) {
-                    ctx.rect_filled((segment.x * w, segment.y * h), (w, h), Color32::WHITE);
+                // with shapes on apples are round and bonuses triangles,
+                // walls and the snake stay square
+                let shapes = self.settings.shapes;
+                for apple in &self.apples {
+                    let rect =
+                        Rect::from_min_size(Pos2::new(apple.x * w, apple.y * h), Vec2::new(w, h));
+                    if shapes {
+                        let rect = rect.shrink(w.min(h) * 0.05);
+                        rounded_rect_filled(ctx, rect, rect.width(), apple_color);
+                    } else {
+                        ctx.rect_filled(rect.min, rect.size(), apple_color);
+                    }
+                }
+                if let Some(bonus) = &self.bonus {
+                    let center = Pos2::new((bonus.pos.x + 0.5) * w, (bonus.pos.y + 0.5) * h);
+                    let food = Vec2::new(w, h) * 0.6;
+                    if shapes {
+                        let rect = Rect::from_center_size(center, food);
+                        triangle_filled(ctx, rect, Color32::GOLD);
+                    } else {
+                        ctx.rect_filled(center - food / 2., food, Color32::GOLD);
+                    }
+                    // the ring empties as the bonus runs out
+                    let left = bonus.ticks_left as f32 / BONUS_TICKS as f32;
+                    let radius = w.min(h) * 0.5;
+                    arc(ctx, center, Vec2::splat(radius), left, 2., Color32::GOLD);
+                }
+                // mark the edges the snake can pass through
+                let edges = [
+                    ((0.0, 0.0), (0.0, size.y)),
+                    ((size.x, 0.0), (size.x, size.y)),
+                    ((0.0, 0.0), (size.x, 0.0)),
+                    ((0.0, size.y), (size.x, size.y)),
+                ];
+                for ((from, to), wrap) in edges.into_iter().zip(self.options.wrap) {
+                    if wrap {
+                        ctx.line(from, to, 4., Color32::LIGHT_BLUE);
+                    }
                 }
-                ctx.rect_filled((self.apple.x * w, self.apple.y * h), (w, h), Color32::GREEN);
             },
         );
-        if self.collision {
-            let score_text = ctx.draw_centered(|ctx: &mut DrawContext<'_>| {
-                let rect = ctx
-                    .text_centered_anchor(
-                        (size.x / 2.0, size.y / 2.0),
-                        "Game Over!\n Press R to restart",
-                        30.,
-                        Color32::WHITE,
-                        Anchor::TopCenter,
-                    )
-                    .rect();
-                ctx.text_centered_anchor(
-                    (size.x / 2.0, rect.min.y + rect.height() + 10.0),
-                    format!("Score: {}\nHighscore: {}", self.score, self.highscore),
-                    30.,
-                    Color32::GREEN,
-                    Anchor::TopCenter,
+        if self.collision && self.dying <= 0.0 {
+            let mode_hint = if self.options.two_player {
+                "M: one player, P: replay, F: trail, I: practice, T: touch buttons, J: assist, Esc: settings, F2: screenshot"
+            } else {
+                "M: two players, P: replay, F: trail, I: practice, T: touch buttons, J: assist, Esc: settings, F2: screenshot"
+            };
+            let spawn_hint = match self.options.spawn {
+                Spawn::Scan => "O: apples in open space",
+                Spawn::OpenSpace => "O: apples anywhere",
+            };
+            let spawn_hint = format!(
+                "{spawn_hint}, G: board {}x{}, L: level {}, N: {} apples, B: walls {}, H: sudden death {}",
+                self.grid_size.x,
+                self.grid_size.y,
+                self.options.level.name(),
+                self.options.apples,
+                self.options.walls.name(),
+                if self.options.shrink { "on" } else { "off" }
+            );
+            let edge_hint = ["left", "right", "top", "bottom"]
+                .iter()
+                .zip(self.options.wrap)
+                .enumerate()
+                .map(|(i, (edge, wrap))| {
+                    format!("{}: {edge} {}", i + 1, if wrap { "wraps" } else { "solid" })
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let spawn_hint = format!(
+                "{spawn_hint}\n{edge_hint}, V: speed-up {}, C: moving apples {}, E: adaptive difficulty {}",
+                self.options.acceleration.name(),
+                if self.options.moving_apples {
+                    "on"
+                } else {
+                    "off"
+                },
+                if self.options.adaptive { "on" } else { "off" }
+            );
+            let spawn_hint = format!(
+                "{spawn_hint}, K: continues {}",
+                if self.options.continues { "on" } else { "off" }
+            );
+            let (title, details) = if self.can_continue() {
+                (
+                    format!(
+                        "Crashed!\nSpace: continue for {} points, Enter: end the run",
+                        CONTINUE_PENALTY.min(self.players[0].score)
+                    ),
+                    format!(
+                        "Score: {}\nContinues left: {}",
+                        self.players[0].score, self.continues_left
+                    ),
                 )
-                .rect();
-            });
-            score_text.background(10., Color32::from_black_alpha(200));
+            } else if self.options.two_player {
+                (
+                    format!(
+                        "{}\n Press R to restart\n{mode_hint}\n{spawn_hint}",
+                        self.winner_text()
+                    ),
+                    self.score_text(),
+                )
+            } else if let Some(initials) = &self.initials {
+                (
+                    "New high score!\nEnter your initials".to_string(),
+                    format!("{}\nUp/Down: letter, Enter: save", initials.display()),
+                )
+            } else {
+                let best = self.leaderboard.top().map_or_else(
+                    || "-".to_string(),
+                    |e| format!("{} {}", e.initials, e.score),
+                );
+                (
+                    format!("Game Over!\n Press R to restart\n{mode_hint}\n{spawn_hint}"),
+                    format!("Score: {}\nBest: {best}", self.players[0].score),
+                )
+            };
+            draw_game_over(ctx, size, &title, &[(details, Color32::GREEN)]);
         } else {
             ctx.text(
                 (10.0, 10.0),
-                format!("Score: {}", self.score),
+                format!(
+                    "{}  Speed: Lv {}{}{}",
+                    self.score_text(),
+                    self.speed_level(),
+                    self.streak_text(),
+                    self.ease_text()
+                ),
                 20.,
                 Color32::WHITE,
             );
+            let banner = if self.replay.is_some() {
+                Some("Replay - R: restart")
+            } else if self.options.invincible {
+                Some("Practice - not scored, R: restart, I: leave")
+            } else {
+                None
+            };
+            if let Some(banner) = banner {
+                ctx.text_centered_anchor(
+                    (size.x / 2.0, 10.0),
+                    banner,
+                    20.,
+                    Color32::GOLD,
+                    Anchor::TopCenter,
+                );
+            }
+            if self.options.dpad {
+                draw_dpad(ctx, size);
+            }
+            if self.countdown > 0.0 {
+                ctx.text_centered(
+                    (size / 2.).to_pos2(),
+                    format!("{}", self.countdown.ceil()),
+                    80.,
+                    Color32::WHITE,
+                );
+            }
         }
+        self.achievements.draw(ctx, size);
+        self.settings.draw(ctx, size);
+        self.screenshot.draw(ctx, size);
     }
 
     fn reset(&mut self) {
-        let highscore = self.highscore;
-        *self = Self::new(None);
-        self.highscore = highscore;
+        let leaderboard = std::mem::take(&mut self.leaderboard);
+        let settings = std::mem::take(&mut self.settings);
+        let achievements = std::mem::take(&mut self.achievements);
+        let screenshot = std::mem::take(&mut self.screenshot);
+        let adaptive = self.adaptive;
+        *self = Self::start(leaderboard, self.options);
+        self.settings = settings;
+        self.achievements = achievements;
+        self.screenshot = screenshot;
+        self.adaptive = adaptive;
+        if self.options.adaptive && !self.options.two_player {
+            self.start_tick = adaptive.start_tick();
+            self.tick = self.start_tick;
+        }
     }
 }
 
-fn random_pos(width: u32, height: u32, segments: &[Pos2]) -> Pos2 {
-    let mut pos = Pos2::new(random_u32(0..width) as f32, random_u32(0..height) as f32);
-    // check if position is in segments
-    while segments.contains(&pos) {
-        // move to next cell until we find a free one
-        if pos.x < width as f32 - 1. {
-            pos.x += 1.;
+impl Snake {
+    fn start(leaderboard: Leaderboard, options: Options) -> Self {
+        Self::start_seeded(leaderboard, options, fastrand::u64(..))
+    }
+
+    fn start_seeded(leaderboard: Leaderboard, options: Options, seed: u64) -> Self {
+        let grid_size = options.board.size();
+        let mut players = vec![Player::new(
+            Pos2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Color32::WHITE,
+        )];
+        if options.two_player {
+            players.push(Player::new(
+                Pos2::new(grid_size.x - 1., grid_size.y - 1.),
+                Vec2::new(-1.0, 0.0),
+                Color32::LIGHT_BLUE,
+            ));
+        }
+        let walls = options.level.walls(grid_size);
+        let mut snake = Self {
+            players,
+            level_walls: walls.len(),
+            walls,
+            tick: START_TICK,
+            start_tick: START_TICK,
+            continues_left: MAX_CONTINUES,
+            countdown: COUNTDOWN,
+            grid_size,
+            leaderboard,
+            options,
+            seed,
+            rng: fastrand::Rng::with_seed(seed),
+            ..Default::default()
+        };
+        snake.fill_apples();
+        snake
+    }
+
+    /// moves all snakes by one cell at the same time
+    fn step(&mut self) {
+        if self.options.invincible {
+            self.step_invincible();
+            return;
+        }
+        let heads: Vec<Pos2> = self
+            .players
+            .iter()
+            .map(|p| self.wrap(p.segments[0] + p.direction))
+            .collect();
+        // check for collision with walls, any snake or the other head
+        let deaths: Vec<bool> = heads
+            .iter()
+            .enumerate()
+            .map(|(i, head)| {
+                !self.in_safe_zone(*head)
+                    || self.players.iter().any(|p| p.segments.contains(head))
+                    || self.walls.contains(head)
+                    || heads
+                        .iter()
+                        .enumerate()
+                        .any(|(j, other)| j != i && other == head)
+            })
+            .collect();
+        if deaths.contains(&true) {
+            // game over
+            for (player, dead) in self.players.iter_mut().zip(deaths) {
+                player.dead = dead;
+            }
+            self.collision = true;
+            self.dying = DEATH_TIME;
+            if !self.can_continue() {
+                self.end_run();
+            }
+            return;
+        }
+        // move snakes
+        let mut ate = false;
+        for (player, head) in self.players.iter_mut().zip(heads) {
+            player.tick_streak();
+            player.segments.push_front(head);
+            player.last_move = player.direction;
+            if self.bonus.as_ref().is_some_and(|bonus| bonus.pos == head) {
+                player.score += BONUS_SCORE;
+                self.bonus = None;
+            }
+            // check for collision with an apple
+            if let Some(index) = self.apples.iter().position(|apple| *apple == head) {
+                self.apples.swap_remove(index);
+                player.eat();
+                if self.options.moving_apples {
+                    player.score += MOUSE_BONUS;
+                }
+                ate = true;
+            } else if let Some(tail) = player.segments.pop_back() {
+                self.trail.push((tail, player.color, TRAIL_TIME));
+            }
+        }
+        if ate {
+            self.fill_apples();
+            self.speed_up();
+            if self.replay.is_none() {
+                self.grant_achievements();
+            }
+        }
+    }
+
+    /// records the finished run, single-player scores may go on the leaderboard
+    fn end_run(&mut self) {
+        if self.options.two_player || self.replay.is_some() {
+            return;
+        }
+        if self.options.adaptive {
+            self.adaptive.record(self.players[0].score);
+        }
+        if self.leaderboard.qualifies(self.players[0].score) {
+            if self.players[0].score > self.leaderboard.best_score() {
+                self.achievements.grant("high_score", "New high score!");
+            }
+            self.initials = Some(InitialsEntry::default());
+        }
+    }
+
+    /// the crashed single-player run may still be continued
+    fn can_continue(&self) -> bool {
+        self.options.continues
+            && !self.options.two_player
+            && self.replay.is_none()
+            && self.continues_left > 0
+    }
+
+    /// respawns the crashed snake shortened at a safe spot for a score penalty,
+    /// apples, walls and the speed stay as they are
+    fn continue_run(&mut self) {
+        let Some(pos) = self.safe_spawn_pos() else {
+            return;
+        };
+        if self.replay.is_none() {
+            self.continues_left -= 1;
+            self.continued_at.push(self.moves.len());
+            self.countdown = COUNTDOWN;
+        }
+        let player = &mut self.players[0];
+        player.score = player.score.saturating_sub(CONTINUE_PENALTY);
+        let length = player.segments.len().min(CONTINUE_LENGTH);
+        // the body unrolls from the respawn cell
+        player.segments = std::iter::repeat_n(pos, length).collect();
+        player.turns.clear();
+        player.streak = 0;
+        player.streak_ticks = 0;
+        player.dead = false;
+        self.collision = false;
+        self.dying = 0.0;
+    }
+
+    /// a free cell with nothing deadly within `CONTINUE_CLEARANCE` cells if
+    /// one is found, the snake gets the countdown to pick a direction,
+    /// none if the board is full
+    fn safe_spawn_pos(&mut self) -> Option<Pos2> {
+        let occupied = self.occupied();
+        let (width, height) = (self.grid_size.x as u32, self.grid_size.y as u32);
+        let wrap = self.options.wrap;
+        let grid_size = self.grid_size;
+        let clear = |pos: Pos2| {
+            let [left, right, top, bottom] = wrap;
+            let edges = (left || pos.x >= CONTINUE_CLEARANCE)
+                && (right || pos.x < grid_size.x - CONTINUE_CLEARANCE)
+                && (top || pos.y >= CONTINUE_CLEARANCE)
+                && (bottom || pos.y < grid_size.y - CONTINUE_CLEARANCE);
+            edges
+                && occupied.iter().all(|other| {
+                    (other.x - pos.x).abs() > CONTINUE_CLEARANCE
+                        || (other.y - pos.y).abs() > CONTINUE_CLEARANCE
+                })
+        };
+        let mut pos = random_pos(&mut self.rng, width, height, &occupied)?;
+        for _ in 0..CONTINUE_SAMPLES {
+            if clear(pos) {
+                break;
+            }
+            pos = random_pos(&mut self.rng, width, height, &occupied)?;
+        }
+        Some(pos)
+    }
+
+    fn speed_up(&mut self) {
+        self.tick = self.options.acceleration.next_tick(self.tick);
+    }
+
+    /// number of speed ups so far, starting at level 1
+    fn speed_level(&self) -> u32 {
+        self.options.acceleration.level(self.start_tick, self.tick)
+    }
+
+    fn grant_achievements(&mut self) {
+        self.achievements.grant("first_apple", "First apple!");
+        let best = self.players.iter().map(|p| p.score).max().unwrap_or(0);
+        if best >= 25 {
+            self.achievements.grant("score_25", "Score 25");
+        }
+        if self.players.iter().any(|p| p.segments.len() >= 50) {
+            self.achievements.grant("length_50", "50 segments long");
+        }
+    }
+
+    /// moves the snakes without collisions, heads stop at the walls
+    fn step_invincible(&mut self) {
+        let min = Pos2::splat(self.inset);
+        let max = (self.grid_size - Vec2::splat(1.0 + self.inset)).to_pos2();
+        let mut ate = false;
+        for player in &mut self.players {
+            player.tick_streak();
+            let old = player.segments[0];
+            let head = wrap_edges(self.options.wrap, self.grid_size, old + player.direction)
+                .clamp(min, max);
+            // walls stop the snake like the border
+            if head == old || self.walls.contains(&head) {
+                continue;
+            }
+            player.segments.push_front(head);
+            player.last_move = player.direction;
+            if self.bonus.as_ref().is_some_and(|bonus| bonus.pos == head) {
+                player.score += BONUS_SCORE;
+                self.bonus = None;
+            }
+            if let Some(index) = self.apples.iter().position(|apple| *apple == head) {
+                self.apples.swap_remove(index);
+                player.eat();
+                ate = true;
+            } else if let Some(tail) = player.segments.pop_back() {
+                self.trail.push((tail, player.color, TRAIL_TIME));
+            }
+        }
+        if ate {
+            self.fill_apples();
+            self.speed_up();
+        }
+    }
+
+    /// steers the first snake with swipes along the dominant axis
+    fn handle_swipe(&mut self, ctx: &mut UpdateContext<'_, Self>) {
+        if ctx.mouse_button_pressed(PointerButton::Primary) {
+            self.swipe_start = Some(ctx.mouse_position());
+        } else if !ctx.mouse_button_down(PointerButton::Primary) {
+            self.swipe_start = None;
+        }
+        let Some(start) = self.swipe_start else {
+            return;
+        };
+        let delta = ctx.mouse_position() - start;
+        if delta.length() < SWIPE_DISTANCE {
+            return;
+        }
+        let direction = if delta.x.abs() > delta.y.abs() {
+            Vec2::new(delta.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, delta.y.signum())
+        };
+        self.players[0].turn(direction);
+        // measure the next turn of the same swipe from here
+        self.swipe_start = Some(ctx.mouse_position());
+    }
+
+    /// steers the first snake with the on-screen buttons
+    fn handle_dpad(&mut self, ctx: &mut UpdateContext<'_, Self>, size: Vec2) {
+        if !ctx.mouse_button_pressed(PointerButton::Primary) {
+            return;
+        }
+        let pointer = ctx.mouse_position();
+        if let Some((_, direction)) = dpad_buttons(size)
+            .into_iter()
+            .find(|(rect, _)| rect.contains(pointer))
+        {
+            self.players[0].turn(direction);
+            // a tap on a button is not the start of a swipe
+            self.swipe_start = None;
+        }
+    }
+
+    /// restarts from the same seed and plays the recorded moves back
+    fn start_replay(&mut self) {
+        let leaderboard = std::mem::take(&mut self.leaderboard);
+        let moves = std::mem::take(&mut self.moves);
+        let settings = std::mem::take(&mut self.settings);
+        let achievements = std::mem::take(&mut self.achievements);
+        let screenshot = std::mem::take(&mut self.screenshot);
+        let (adaptive, start_tick) = (self.adaptive, self.start_tick);
+        let continued_at = std::mem::take(&mut self.continued_at);
+        *self = Self::start_seeded(leaderboard, self.options, self.seed);
+        self.moves = moves;
+        self.continued_at = continued_at;
+        self.settings = settings;
+        self.achievements = achievements;
+        self.screenshot = screenshot;
+        self.adaptive = adaptive;
+        self.start_tick = start_tick;
+        self.tick = start_tick;
+        self.replay = Some(0);
+        self.countdown = 0.0;
+    }
+
+    /// spawns apples until the configured count is on the board
+    fn fill_apples(&mut self) {
+        while self.apples.len() < self.options.apples {
+            let Some(pos) = self.random_free_pos() else {
+                break;
+            };
+            self.apples.push(pos);
+        }
+    }
+
+    /// moves a head that left the board through a wrapping edge to the opposite side
+    fn wrap(&self, head: Pos2) -> Pos2 {
+        wrap_edges(self.options.wrap, self.grid_size, head)
+    }
+
+    /// inside the board and, in sudden death, inside the shrinking border
+    fn in_safe_zone(&self, pos: Pos2) -> bool {
+        pos.x >= self.inset
+            && pos.x < self.grid_size.x - self.inset
+            && pos.y >= self.inset
+            && pos.y < self.grid_size.y - self.inset
+    }
+
+    /// closes the border in by one cell every few ticks,
+    /// apples caught in the border move back inside
+    fn shrink_border(&mut self) {
+        self.shrink_ticks += 1;
+        if self.shrink_ticks < SHRINK_INTERVAL
+            || self.grid_size.min_elem() - (self.inset + 1.) * 2. < MIN_SAFE_SIZE
+        {
+            return;
+        }
+        self.shrink_ticks = 0;
+        self.inset += 1.;
+        let apples = std::mem::take(&mut self.apples);
+        self.apples = apples
+            .into_iter()
+            .filter(|apple| self.in_safe_zone(*apple))
+            .collect();
+        if self
+            .bonus
+            .as_ref()
+            .is_some_and(|bonus| !self.in_safe_zone(bonus.pos))
+        {
+            self.bonus = None;
+        }
+        self.fill_apples();
+    }
+
+    /// cells covered by a snake, an apple or a wall, and the sudden death border
+    fn occupied(&self) -> Vec<Pos2> {
+        let border = (0..self.grid_size.y as u32)
+            .flat_map(|y| (0..self.grid_size.x as u32).map(move |x| Pos2::new(x as f32, y as f32)))
+            .filter(|pos| !self.in_safe_zone(*pos));
+        self.players
+            .iter()
+            .flat_map(|p| p.segments.iter().copied())
+            .chain(self.apples.iter().copied())
+            .chain(self.bonus.as_ref().map(|bonus| bonus.pos))
+            .chain(self.walls.iter().copied())
+            .chain(border)
+            .collect()
+    }
+
+    /// adds a wall every few ticks, more are allowed as the score grows
+    fn spawn_walls(&mut self) {
+        self.wall_ticks += 1;
+        if self.wall_ticks < self.options.walls.interval() {
+            return;
+        }
+        self.wall_ticks = 0;
+        let score: u32 = self.players.iter().map(|p| p.score).sum();
+        let allowed = (score as usize / 2).min(self.options.walls.cap());
+        if self.walls.len() - self.level_walls >= allowed {
+            return;
+        }
+        let mut occupied = self.occupied();
+        // keep the next cells in front of every head free
+        for player in &self.players {
+            let head = player.segments[0];
+            occupied.extend((1..=WALL_DISTANCE).map(|i| head + player.direction * i as f32));
+        }
+        let (width, height) = (self.grid_size.x as u32, self.grid_size.y as u32);
+        if let Some(pos) = random_pos(&mut self.rng, width, height, &occupied) {
+            self.walls.push(pos);
+        }
+    }
+
+    /// moves every apple one cell away from the nearest head every few
+    /// ticks, now and then it wanders off in a random direction instead
+    fn move_apples(&mut self) {
+        self.mouse_ticks += 1;
+        if self.mouse_ticks < MOUSE_INTERVAL {
+            return;
+        }
+        self.mouse_ticks = 0;
+        let heads: Vec<Pos2> = self.players.iter().map(|p| p.segments[0]).collect();
+        let distance = |pos: Pos2| {
+            heads
+                .iter()
+                .map(|head| head.distance_sq(pos))
+                .fold(f32::INFINITY, f32::min)
+        };
+        let directions = [
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, -1.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        for i in 0..self.apples.len() {
+            let occupied = self.occupied();
+            let free: Vec<Pos2> = directions
+                .iter()
+                .map(|&direction| self.apples[i] + direction)
+                .filter(|pos| self.in_safe_zone(*pos) && !occupied.contains(pos))
+                .collect();
+            let next = if self.rng.usize(..3) == 0 {
+                self.rng.choice(free)
+            } else {
+                free.into_iter()
+                    .max_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+            };
+            if let Some(next) = next {
+                self.apples[i] = next;
+            }
+        }
+    }
+
+    /// counts down the bonus food and now and then spawns a new one
+    fn update_bonus(&mut self) {
+        if let Some(bonus) = &mut self.bonus {
+            bonus.ticks_left -= 1;
+            if bonus.ticks_left == 0 {
+                self.bonus = None;
+            }
+            return;
+        }
+        if self.rng.usize(..BONUS_CHANCE) != 0 {
+            return;
+        }
+        if let Some(pos) = self.random_free_pos() {
+            self.bonus = Some(Bonus {
+                pos,
+                ticks_left: BONUS_TICKS,
+            });
+        }
+    }
+
+    /// a cell not covered by any snake, apple or wall, none if the board is full
+    fn random_free_pos(&mut self) -> Option<Pos2> {
+        let occupied = self.occupied();
+        let (width, height) = (self.grid_size.x as u32, self.grid_size.y as u32);
+        match self.options.spawn {
+            Spawn::Scan => random_pos(&mut self.rng, width, height, &occupied),
+            Spawn::OpenSpace => {
+                let heads: Vec<Pos2> = self.players.iter().map(|p| p.segments[0]).collect();
+                let distance = |pos: Pos2| {
+                    heads
+                        .iter()
+                        .map(|head| head.distance_sq(pos))
+                        .fold(f32::INFINITY, f32::min)
+                };
+                let free = free_cells(width, height, &occupied);
+                (0..SPAWN_SAMPLES)
+                    .filter_map(|_| self.rng.choice(&free).copied())
+                    .max_by(|a, b| distance(*a).total_cmp(&distance(*b)))
+            }
+        }
+    }
+
+    /// the board in flat shapes, laid out like `draw` without the overlays
+    fn screenshot_canvas(&self) -> Canvas {
+        let cell = Vec2::splat(SCREENSHOT_CELL);
+        let mut canvas = Canvas::new(self.grid_size * cell, self.settings.theme.background());
+        let cell_rect = |pos: Pos2| Rect::from_min_size((pos.to_vec2() * cell).to_pos2(), cell);
+        if self.inset > 0.0 {
+            let safe = Rect::from_min_max(
+                Pos2::splat(self.inset * SCREENSHOT_CELL),
+                (canvas.size() - Vec2::splat(self.inset * SCREENSHOT_CELL)).to_pos2(),
+            );
+            let border = Color32::from_rgb(120, 30, 30);
+            canvas.rect_filled(Rect::from_min_size(Pos2::ZERO, canvas.size()), border);
+            canvas.rect_filled(safe, self.settings.theme.background());
+        }
+        for wall in &self.walls {
+            canvas.rect_filled(cell_rect(*wall), Color32::from_rgb(140, 70, 20));
+        }
+        let apple_color = if self.options.moving_apples {
+            Color32::LIGHT_GRAY
         } else {
-            pos.x = 0.;
-            pos.y += 1.;
+            Color32::GREEN
+        };
+        for apple in &self.apples {
+            if self.settings.shapes {
+                let rect = cell_rect(*apple);
+                canvas.circle_filled(rect.center(), SCREENSHOT_CELL / 2., apple_color);
+            } else {
+                canvas.rect_filled(cell_rect(*apple), apple_color);
+            }
         }
+        if let Some(bonus) = &self.bonus {
+            let rect = cell_rect(bonus.pos);
+            canvas.rect_filled(
+                Rect::from_center_size(rect.center(), cell * 0.6),
+                Color32::GOLD,
+            );
+        }
+        for player in &self.players {
+            for segment in player.segments.iter().skip(1) {
+                canvas.rect_filled(cell_rect(*segment).shrink(1.), player.color);
+            }
+            let head_color = if player.dead {
+                Color32::RED
+            } else {
+                player.color
+            };
+            canvas.rect_filled(cell_rect(player.segments[0]), head_color);
+        }
+        canvas
+    }
+
+    /// marks the cell each head enters next and points it to the nearest apple
+    fn draw_assist(&self, ctx: &mut DrawContext<'_>, cell: Vec2) {
+        let center = |pos: Pos2| Pos2::new((pos.x + 0.5) * cell.x, (pos.y + 0.5) * cell.y);
+        for player in &self.players {
+            let head = player.segments[0];
+            let next = self.wrap(head + player.direction);
+            ctx.rect_filled(
+                (next.x * cell.x, next.y * cell.y),
+                cell,
+                Color32::from_white_alpha(40),
+            );
+            let nearest = self
+                .apples
+                .iter()
+                .min_by(|a, b| head.distance_sq(**a).total_cmp(&head.distance_sq(**b)));
+            if let Some(apple) = nearest {
+                ctx.line(
+                    center(head),
+                    center(*apple),
+                    2.,
+                    Color32::from_white_alpha(60),
+                );
+            }
+        }
+    }
+
+    fn score_text(&self) -> String {
+        if let [p1, p2] = &self.players[..] {
+            format!("P1: {}  P2: {}", p1.score, p2.score)
+        } else {
+            format!("Score: {}", self.players[0].score)
+        }
+    }
+
+    /// active streaks, empty while no snake is on one
+    fn streak_text(&self) -> String {
+        let two_player = self.players.len() > 1;
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.streak > 1)
+            .map(|(i, p)| {
+                if two_player {
+                    format!("  P{} streak x{}", i + 1, p.streak)
+                } else {
+                    format!("  Streak x{}", p.streak)
+                }
+            })
+            .collect()
+    }
+
+    /// marks runs that started slower, empty otherwise
+    fn ease_text(&self) -> &'static str {
+        if self.eased() { "  (eased)" } else { "" }
+    }
+
+    fn eased(&self) -> bool {
+        self.start_tick > START_TICK
+    }
+
+    fn winner_text(&self) -> &'static str {
+        let [p1, p2] = &self.players[..] else {
+            return "Game Over!";
+        };
+        let winner = match p1.score.cmp(&p2.score) {
+            Ordering::Greater => Some(1),
+            Ordering::Less => Some(2),
+            // equal score, the survivor wins
+            Ordering::Equal => match (p1.dead, p2.dead) {
+                (false, true) => Some(1),
+                (true, false) => Some(2),
+                _ => None,
+            },
+        };
+        match winner {
+            Some(1) => "Player 1 wins!",
+            Some(_) => "Player 2 wins!",
+            None => "Draw!",
+        }
+    }
+
+    fn handle_initials_input(&mut self, ctx: &mut UpdateContext<'_, Self>) {
+        let eased = self.eased();
+        let Some(initials) = &mut self.initials else {
+            return;
+        };
+        if ctx.key_pressed(Key::ArrowUp) {
+            initials.next_letter();
+        } else if ctx.key_pressed(Key::ArrowDown) {
+            initials.prev_letter();
+        } else if ctx.key_pressed(Key::ArrowLeft) {
+            initials.move_cursor(false);
+        } else if ctx.key_pressed(Key::ArrowRight) {
+            initials.move_cursor(true);
+        } else if ctx.key_pressed(Key::Enter) {
+            // eased runs are marked with a star after the initials
+            let name = if eased {
+                format!("{}*", initials.text())
+            } else {
+                initials.text()
+            };
+            self.leaderboard.insert(name, self.players[0].score);
+            self.initials = None;
+        }
+    }
+}
+
+/// size of one cell when the board of `grid_size` cells fills `field`
+fn cell_size(field: Vec2, grid_size: Vec2) -> Vec2 {
+    Vec2::new(field.x / grid_size.x, field.y / grid_size.y)
+}
+
+fn wrap_edges(wrap: [bool; 4], grid_size: Vec2, mut head: Pos2) -> Pos2 {
+    let [left, right, top, bottom] = wrap;
+    if left && head.x < 0.0 {
+        head.x = grid_size.x - 1.;
+    } else if right && head.x >= grid_size.x {
+        head.x = 0.0;
+    }
+    if top && head.y < 0.0 {
+        head.y = grid_size.y - 1.;
+    } else if bottom && head.y >= grid_size.y {
+        head.y = 0.0;
+    }
+    head
+}
+
+/// a random cell of the board that is not in `occupied`, none if all are taken
+fn random_pos(rng: &mut fastrand::Rng, width: u32, height: u32, occupied: &[Pos2]) -> Option<Pos2> {
+    rng.choice(free_cells(width, height, occupied))
+}
+
+/// cells of the board that are not in `occupied`
+fn free_cells(width: u32, height: u32, occupied: &[Pos2]) -> Vec<Pos2> {
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| Pos2::new(x as f32, y as f32)))
+        .filter(|pos| !occupied.contains(pos))
+        .collect()
+}
+
+/// direction buttons arranged as a cross in the bottom right corner
+fn dpad_buttons(size: Vec2) -> [(Rect, Vec2); 4] {
+    let center = Pos2::new(size.x - BUTTON_SIZE * 2., size.y - BUTTON_SIZE * 2.);
+    [
+        Vec2::new(-1.0, 0.0),
+        Vec2::new(1.0, 0.0),
+        Vec2::new(0.0, -1.0),
+        Vec2::new(0.0, 1.0),
+    ]
+    .map(|direction| {
+        let rect = Rect::from_center_size(
+            center + direction * BUTTON_SIZE,
+            Vec2::splat(BUTTON_SIZE * 0.9),
+        );
+        (rect, direction)
+    })
+}
+
+fn draw_dpad(ctx: &mut DrawContext<'_>, size: Vec2) {
+    for (rect, direction) in dpad_buttons(size) {
+        ctx.rect_filled(rect.min, rect.size(), Color32::from_white_alpha(60));
+        // chevron pointing in the button direction
+        let tip = rect.center() + direction * BUTTON_SIZE * 0.2;
+        let back = rect.center() - direction * BUTTON_SIZE * 0.1;
+        let side = direction.rot90() * BUTTON_SIZE * 0.2;
+        ctx.line(tip, back + side, 4., Color32::WHITE);
+        ctx.line(tip, back - side, 4., Color32::WHITE);
+    }
+}
+
+/// draws the first `length` segments as one connected piece with rounded segments
+fn draw_player(ctx: &mut DrawContext<'_>, player: &Player, length: usize, cell: Vec2) {
+    let segments = || player.segments.iter().take(length);
+    let inset = cell * BODY_INSET;
+    let half = cell / 2. - inset;
+    let center = |segment: Pos2| Pos2::new((segment.x + 0.5) * cell.x, (segment.y + 0.5) * cell.y);
+    // bridges between neighbouring segments, only as wide as the body
+    for (a, b) in segments().zip(segments().skip(1)) {
+        let (a, b) = (center(*a), center(*b));
+        let expand = if (b - a).x.abs() < (b - a).y.abs() {
+            Vec2::new(half.x, 0.0)
+        } else {
+            Vec2::new(0.0, half.y)
+        };
+        let bridge = Rect::from_two_pos(a, b).expand2(expand);
+        ctx.rect_filled(bridge.min, bridge.size(), player.color);
+    }
+    let radius = cell.min_elem();
+    for segment in segments().skip(1) {
+        let rect = Rect::from_center_size(center(*segment), half * 2.);
+        rounded_rect_filled(ctx, rect, radius * BODY_RADIUS, player.color);
+    }
+    let head_color = if player.dead {
+        Color32::RED
+    } else {
+        player.color
+    };
+    let head = Rect::from_center_size(center(player.segments[0]), half * 2.);
+    rounded_rect_filled(ctx, head, radius * HEAD_RADIUS, head_color);
+}
+
+/// rectangle with rounded corners, the rounded ends are made of horizontal strips
+fn rounded_rect_filled(ctx: &mut DrawContext<'_>, rect: Rect, radius: f32, color: Color32) {
+    const STRIPS: usize = 4;
+    let radius = radius.min(rect.width() / 2.).min(rect.height() / 2.);
+    ctx.rect_filled(
+        (rect.min.x, rect.min.y + radius),
+        (rect.width(), rect.height() - radius * 2.),
+        color,
+    );
+    let strip_height = radius / STRIPS as f32;
+    for i in 0..STRIPS {
+        // vertical distance of the strip center from the corner centers in 0..1
+        let y = 1. - (i as f32 + 0.5) / STRIPS as f32;
+        let indent = radius * (1. - (1. - y * y).sqrt());
+        let width = rect.width() - indent * 2.;
+        let offset = i as f32 * strip_height;
+        ctx.rect_filled(
+            (rect.min.x + indent, rect.min.y + offset),
+            (width, strip_height),
+            color,
+        );
+        ctx.rect_filled(
+            (rect.min.x + indent, rect.max.y - offset - strip_height),
+            (width, strip_height),
+            color,
+        );
     }
-    pos
 }
 
 fn main() {
     EguiGame::new().run::<Snake>("Snake");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// tick after eating `apples` apples from the start
+    fn tick_after(acceleration: Acceleration, apples: usize) -> f32 {
+        (0..apples).fold(START_TICK, |tick, _| acceleration.next_tick(tick))
+    }
+
+    #[test]
+    fn no_acceleration_keeps_the_tick() {
+        assert_eq!(tick_after(Acceleration::None, 10), START_TICK);
+        assert_eq!(Acceleration::None.level(START_TICK, START_TICK), 1);
+    }
+
+    #[test]
+    fn linear_acceleration_shortens_by_a_fixed_step() {
+        let tick = tick_after(Acceleration::Linear, 5);
+        assert!((tick - (START_TICK - 5. * LINEAR_SPEED_UP)).abs() < 1e-6);
+        assert_eq!(Acceleration::Linear.level(START_TICK, tick), 6);
+    }
+
+    #[test]
+    fn geometric_acceleration_shortens_by_a_factor() {
+        let tick = tick_after(Acceleration::Geometric, 5);
+        assert!((tick - START_TICK * SPEED_UP.powi(5)).abs() < 1e-6);
+        assert_eq!(Acceleration::Geometric.level(START_TICK, tick), 6);
+    }
+
+    #[test]
+    fn tick_is_clamped_to_the_minimum() {
+        for acceleration in [Acceleration::Linear, Acceleration::Geometric] {
+            assert_eq!(tick_after(acceleration, 1000), MIN_TICK);
+        }
+    }
+
+    #[test]
+    fn eased_start_is_level_one() {
+        let start = START_TICK + 2. * EASE_STEP;
+        for acceleration in [Acceleration::Linear, Acceleration::Geometric] {
+            assert_eq!(acceleration.level(start, start), 1);
+            assert_eq!(acceleration.level(start, acceleration.next_tick(start)), 2);
+        }
+    }
+
+    #[test]
+    fn cells_are_square_on_every_board() {
+        for board in [Board::Wide, Board::Square, Board::Tall] {
+            let grid_size = board.size();
+            // the field `draw` gets back for the aspect it asks for
+            let field = Vec2::new(600.0 * grid_size.x / grid_size.y, 600.0);
+            let cell = cell_size(field, grid_size);
+            assert!((cell.x - cell.y).abs() < 1e-3, "{grid_size:?}: {cell:?}");
+        }
+    }
+
+    const UP: Vec2 = Vec2::new(0.0, -1.0);
+    const DOWN: Vec2 = Vec2::new(0.0, 1.0);
+    const LEFT: Vec2 = Vec2::new(-1.0, 0.0);
+    const RIGHT: Vec2 = Vec2::new(1.0, 0.0);
+
+    /// a snake of two segments heading left
+    fn player() -> Player {
+        let mut player = Player::new(Pos2::new(5.0, 5.0), LEFT, Color32::WHITE);
+        player.segments.push_back(Pos2::new(6.0, 5.0));
+        player
+    }
+
+    /// applies the next turn and records the move like a tick does
+    fn tick(player: &mut Player) {
+        player.next_turn();
+        player.last_move = player.direction;
+    }
+
+    #[test]
+    fn quick_turns_apply_on_consecutive_ticks() {
+        let mut player = player();
+        player.turn(UP);
+        player.turn(RIGHT);
+        tick(&mut player);
+        assert_eq!(player.direction, UP);
+        tick(&mut player);
+        assert_eq!(player.direction, RIGHT);
+    }
+
+    #[test]
+    fn reversing_the_queued_direction_is_rejected() {
+        let mut player = player();
+        player.turn(UP);
+        player.turn(DOWN);
+        assert_eq!(player.turns, [UP]);
+        // reversing the current direction too
+        let mut other = player();
+        other.turn(RIGHT);
+        assert!(other.turns.is_empty());
+    }
+
+    #[test]
+    fn queue_holds_two_turns() {
+        let mut player = player();
+        player.turn(UP);
+        player.turn(RIGHT);
+        player.turn(DOWN);
+        assert_eq!(player.turns, [UP, RIGHT]);
+    }
+
+    #[test]
+    fn random_pos_stays_inside_the_free_cells() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        // everything but the center of a 5x5 board is taken, like a sudden death border
+        let occupied: Vec<Pos2> = free_cells(5, 5, &[])
+            .into_iter()
+            .filter(|pos| !(1.0..4.0).contains(&pos.x) || !(1.0..4.0).contains(&pos.y))
+            .collect();
+        for _ in 0..100 {
+            let pos = random_pos(&mut rng, 5, 5, &occupied).unwrap();
+            assert!((1.0..4.0).contains(&pos.x) && (1.0..4.0).contains(&pos.y));
+        }
+    }
+
+    #[test]
+    fn random_pos_on_a_full_board_is_none() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        let occupied = free_cells(4, 3, &[]);
+        assert_eq!(random_pos(&mut rng, 4, 3, &occupied), None);
+    }
+}